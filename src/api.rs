@@ -1,13 +1,41 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    panic::{self, AssertUnwindSafe},
+};
+
 use serde_derive::{Serialize, Deserialize};
 use exonum::{
     api::{self, ServiceApiBuilder, ServiceApiState},
-    blockchain::{self, BlockProof, TransactionMessage},
-    crypto::{Hash, PublicKey},
+    blockchain::{self, BlockProof, TransactionMessage, TransactionSet},
+    crypto::{self, Hash, PublicKey, Signature},
     explorer::BlockchainExplorer,
     helpers::Height,
-    storage::{ListProof, MapProof},
+    messages::{Message, ProtocolMessage, RawTransaction, ServiceTransaction, Signed},
+    storage::{ListProof, MapProof, Snapshot},
+};
+use failure::format_err;
+use crate::{
+    approver::{Approver, MIN_APPROVER_STAKE},
+    event::{WalletEvent, MAX_EVENTS_PAGE_LEN},
+    history::{HistoryCheckpoint, HistoryEntry, HISTORY_COMPACTION_THRESHOLD, MAX_HISTORY_PAGE_LEN},
+    invoice::Invoice,
+    oracle::{self, Attestation},
+    schema::{
+        TransferParty, APPROVERS_TABLE, ATTESTATIONS_TABLE, BLOCK_STATISTICS_TABLE,
+        INVOICES_TABLE, NAMES_TABLE, SCHEMA_VERSION, SENDER_FILTERS_TABLE,
+        SETTLED_TRANSFERS_TABLE, TRANSFERS_TABLE, TRANSFER_STATUSES_TABLE, WALLETS_TABLE,
+    },
+    sender_filter::SenderFilter,
+    settlement::{SettledTransfer, TransferStatus},
+    statistics::{BlockStatistics, MAX_STATISTICS_RANGE},
+    transactions::{self, ErrorInfo, Execute, ExecutionContext},
+    transactions::{Reindex, Transfer, WalletTransactions},
+    wallet::{self, Wallet, MAX_WALLETS_BATCH_LEN, MAX_WALLETS_PAGE_LEN, MAX_WALLETS_SCAN},
+    Schema,
+    CRYPTOCURRENCY_SERVICE_ID,
+    INITIAL_BALANCE,
+    SERVICE_NAME,
 };
-use crate::{wallet::Wallet, Schema, CRYPTOCURRENCY_SERVICE_ID};
 
 /// Describes the query parameters for the `get_wallet` endpoint.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
@@ -16,6 +44,28 @@ pub struct WalletQuery {
     pub pub_key: PublicKey,
 }
 
+/// Describes the query parameters for the `wallet_info` endpoint.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct WalletInfoQuery {
+    /// Public key of the queried wallet.
+    pub pub_key: PublicKey,
+    /// Whether to omit the wallet history from the response.
+    #[serde(default)]
+    pub skip_history: bool,
+}
+
+/// Describes the query parameters for the `wallet_history` endpoint.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct WalletHistoryQuery {
+    /// Public key of the queried wallet.
+    pub pub_key: PublicKey,
+    /// Index of the first entry of the page.
+    #[serde(default)]
+    pub offset: u64,
+    /// Maximum number of the entries in the page, `MAX_HISTORY_PAGE_LEN` if not specified.
+    pub limit: Option<u64>,
+}
+
 /// Proof of existence for specific wallet.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct WalletProof {
@@ -28,9 +78,9 @@ pub struct WalletProof {
 /// Wallet history.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct WalletHistory {
-    /// Proof of the list of transaction hashes.
-    pub proof: ListProof<Hash>,
-    /// List of above transactions.
+    /// Proof of the list of history entries.
+    pub proof: ListProof<HistoryEntry>,
+    /// List of the transactions of above entries, except the checkpoint entry.
     pub transactions: Vec<TransactionMessage>,
 }
 
@@ -45,13 +95,563 @@ pub struct WalletInfo {
     pub wallet_history: Option<WalletHistory>,
 }
 
+/// Describes the query parameters for the `wallets` endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WalletsQuery {
+    /// Public key of the wallet from which the page starts.
+    pub from: Option<PublicKey>,
+    /// Maximum number of the wallets in the page, `MAX_WALLETS_PAGE_LEN` if not specified.
+    pub limit: Option<u64>,
+    /// Prefix of the wallet names, compared in the normalized form.
+    pub name_prefix: Option<String>,
+    /// Minimum balance of the wallets.
+    pub min_balance: Option<u64>,
+    /// Whether to list only the wallets with non-zero retained amount.
+    #[serde(default)]
+    pub with_retained: bool,
+    /// Whether to include the proof of the listed wallets.
+    #[serde(default)]
+    pub with_proof: bool,
+}
+
+/// Page of the wallet listing.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WalletsPage {
+    /// Proof of the last block.
+    pub block_proof: BlockProof,
+    /// Proof of the whole database table.
+    pub to_table: MapProof<Hash, Hash>,
+    /// Listed wallets.
+    pub wallets: Vec<Wallet>,
+    /// Proof of the listed wallets in the table, if requested.
+    pub proof: Option<MapProof<PublicKey, Wallet>>,
+    /// Public key of the wallet from which the next page starts, if there is one.
+    pub next: Option<PublicKey>,
+}
+
+/// Describes the body of the `wallets/batch` endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WalletsBatchQuery {
+    /// Public keys of the queried wallets, at most `MAX_WALLETS_BATCH_LEN`.
+    pub pub_keys: Vec<PublicKey>,
+}
+
+/// Wallets of the batch query.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WalletsBatchInfo {
+    /// Proof of the last block.
+    pub block_proof: BlockProof,
+    /// Proof of the whole database table.
+    pub to_table: MapProof<Hash, Hash>,
+    /// Proof of the queried wallets in the table, including the missing ones.
+    pub to_wallets: MapProof<PublicKey, Wallet>,
+}
+
+/// Describes the query parameters for the `richlist` endpoint.
+///
+/// The page starts from the wallet given by `from_balance` and `from`, which are passed
+/// together, or from the richest wallet if they are not specified.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct RichlistQuery {
+    /// Balance of the wallet from which the page starts.
+    pub from_balance: Option<u64>,
+    /// Public key of the wallet from which the page starts.
+    pub from: Option<PublicKey>,
+    /// Maximum number of the wallets in the page, `MAX_WALLETS_PAGE_LEN` if not specified.
+    pub limit: Option<u64>,
+    /// Whether to include the proof of the listed wallets.
+    #[serde(default)]
+    pub with_proof: bool,
+}
+
+/// Position of the wallet in the wallets ordered by balance.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct RichlistCursor {
+    /// Balance of the wallet.
+    pub balance: u64,
+    /// Public key of the wallet.
+    pub pub_key: PublicKey,
+}
+
+/// Page of the wallets ordered by balance, from the richest one.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RichlistPage {
+    /// Proof of the last block.
+    pub block_proof: BlockProof,
+    /// Proof of the whole database table.
+    pub to_table: MapProof<Hash, Hash>,
+    /// Listed wallets.
+    pub wallets: Vec<Wallet>,
+    /// Proof of the listed wallets in the table, if requested.
+    pub proof: Option<MapProof<PublicKey, Wallet>>,
+    /// Position of the wallet from which the next page starts, if there is one.
+    pub next: Option<RichlistCursor>,
+}
+
+/// Page of the history of the specific wallet.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WalletHistoryInfo {
+    /// Proof of the last block.
+    pub block_proof: BlockProof,
+    /// Proof of the appropriate wallet.
+    pub wallet_proof: WalletProof,
+    /// Page of the wallet history, if there are entries in the requested range.
+    pub history: Option<WalletHistory>,
+}
+
+/// Proof of existence for the sender filter of the specific wallet.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SenderFilterProof {
+    /// Proof of the whole database table.
+    pub to_table: MapProof<Hash, Hash>,
+    /// Proof of the specific sender filter in this table.
+    pub to_filter: MapProof<PublicKey, SenderFilter>,
+}
+
+/// Sender filter information.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SenderFilterInfo {
+    /// Proof of the last block.
+    pub block_proof: BlockProof,
+    /// Proof of the appropriate sender filter.
+    pub filter_proof: SenderFilterProof,
+    /// Proof of the senders listed by the appropriate wallet.
+    pub sender_list: Option<MapProof<PublicKey, Hash>>,
+}
+
+/// Describes the query parameters for the `resolve_name` endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct NameQuery {
+    /// Wallet name to resolve.
+    pub name: String,
+}
+
+/// Proof of the name registry entry.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NameProof {
+    /// Proof of the whole database table.
+    pub to_table: MapProof<Hash, Hash>,
+    /// Proof of the specific name in this table.
+    ///
+    /// The key is the hash of the normalized wallet name.
+    pub to_name: MapProof<Hash, PublicKey>,
+}
+
+/// Name resolution result.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NameInfo {
+    /// Proof of the last block.
+    pub block_proof: BlockProof,
+    /// Proof of the appropriate name registry entry.
+    pub name_proof: NameProof,
+}
+
+/// Reverse name resolution result.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReverseNameInfo {
+    /// Proof of the last block.
+    pub block_proof: BlockProof,
+    /// Proof of the wallet which stores the name.
+    pub wallet_proof: WalletProof,
+    /// Proof that the name is registered for the wallet.
+    pub name_proof: Option<NameProof>,
+}
+
+/// Describes the query parameters for the `invoice_info` endpoint.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct InvoiceQuery {
+    /// Identifier of the queried invoice.
+    pub id: Hash,
+}
+
+/// Proof of existence for specific invoice.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InvoiceProof {
+    /// Proof of the whole database table.
+    pub to_table: MapProof<Hash, Hash>,
+    /// Proof of the specific invoice in this table.
+    pub to_invoice: MapProof<Hash, Invoice>,
+}
+
+/// Invoice information.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InvoiceInfo {
+    /// Proof of the last block.
+    pub block_proof: BlockProof,
+    /// Proof of the appropriate invoice.
+    pub invoice_proof: InvoiceProof,
+}
+
+/// Describes the query parameters for the transfer endpoints.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct TransferQuery {
+    /// Hash of the transfer transaction.
+    pub hash: Hash,
+}
+
+/// Proof of existence for specific settled transfer.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SettledTransferProof {
+    /// Proof of the whole database table.
+    pub to_table: MapProof<Hash, Hash>,
+    /// Proof of the specific settled transfer in this table.
+    pub to_transfer: MapProof<Hash, SettledTransfer>,
+}
+
+/// Settled transfer information.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SettledTransferInfo {
+    /// Proof of the last block.
+    pub block_proof: BlockProof,
+    /// Proof of the appropriate settled transfer.
+    pub transfer_proof: SettledTransferProof,
+    /// Proof of the list of refund transaction hashes, if there are any.
+    pub refunds: Option<ListProof<Hash>>,
+}
+
+/// Proof of existence or absence of the status of specific transfer.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TransferStatusProof {
+    /// Proof of the whole database table.
+    pub to_table: MapProof<Hash, Hash>,
+    /// Proof of the status of the specific transfer in this table.
+    pub to_status: MapProof<Hash, TransferStatus>,
+}
+
+/// Transfer status information.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TransferStatusInfo {
+    /// Proof of the last block.
+    pub block_proof: BlockProof,
+    /// Proof of the appropriate transfer status.
+    pub status_proof: TransferStatusProof,
+}
+
+/// Describes the query parameters for the `attestation` endpoint.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct AttestationQuery {
+    /// Public key of the oracle.
+    pub oracle: PublicKey,
+    /// Identifier of the condition.
+    pub condition: Hash,
+}
+
+/// Proof of existence for specific attestation.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AttestationProof {
+    /// Proof of the whole database table.
+    pub to_table: MapProof<Hash, Hash>,
+    /// Proof of the specific attestation in this table.
+    pub to_attestation: MapProof<Hash, Attestation>,
+}
+
+/// Attestation information.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AttestationInfo {
+    /// Proof of the last block.
+    pub block_proof: BlockProof,
+    /// Proof of the appropriate attestation.
+    pub attestation_proof: AttestationProof,
+}
+
+/// Proof of existence for specific approver.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApproverProof {
+    /// Proof of the whole database table.
+    pub to_table: MapProof<Hash, Hash>,
+    /// Proof of the specific approver in this table.
+    pub to_approver: MapProof<PublicKey, Approver>,
+}
+
+/// Approver information.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApproverInfo {
+    /// Proof of the last block.
+    pub block_proof: BlockProof,
+    /// Proof of the appropriate approver.
+    pub approver_proof: ApproverProof,
+}
+
+/// Describes the query parameters for the `pending_transfers` endpoint.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct PendingTransfersQuery {
+    /// Party of the pending transfers.
+    pub party: TransferParty,
+    /// Public key of the party.
+    pub pub_key: PublicKey,
+}
+
+/// Pending transfers of the specific party.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PendingTransfersInfo {
+    /// Proof of the last block.
+    pub block_proof: BlockProof,
+    /// Proof of the table with the roots of the party indexes.
+    pub to_table: MapProof<Hash, Hash>,
+    /// Proof of the root of the party index in this table.
+    pub to_root: MapProof<PublicKey, Hash>,
+    /// Proof of all pending transfers in the party index, if there are any.
+    pub transfers: Option<MapProof<Hash, Transfer>>,
+}
+
+/// Describes the query parameters for the `balance_at` endpoint.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct BalanceAtQuery {
+    /// Public key of the queried wallet.
+    pub pub_key: PublicKey,
+    /// Height of the block after which the balance is queried.
+    pub height: Height,
+}
+
+/// Wallet balance at the specific height.
+///
+/// The balance is the one of the last history entry recorded at a height not greater than
+/// the queried one. The proof covers this entry and the entry following it, if any, so that
+/// the client may check that there were no other changes of the wallet up to the queried height.
+///
+/// If the entries at the queried height are compacted into a checkpoint, `entries` proves
+/// the checkpoint entry of the wallet history and `archived` proves the compacted entries.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BalanceAtInfo {
+    /// Proof of the last block.
+    pub block_proof: BlockProof,
+    /// Proof of the appropriate wallet.
+    pub wallet_proof: WalletProof,
+    /// Proof of the block at the queried height.
+    pub height_block_proof: BlockProof,
+    /// Proof of the history entries around the queried height, if the wallet exists.
+    pub entries: Option<ListProof<HistoryEntry>>,
+    /// Proof of the compacted entries around the queried height, if they are compacted.
+    pub archived: Option<ArchivedBalanceProof>,
+    /// Balance of the wallet at the queried height, if the wallet existed at that height.
+    pub balance: Option<u64>,
+}
+
+/// Proof of the history entries around the specific height compacted into a checkpoint.
+///
+/// The entries are compacted into the first checkpoint compacting entries recorded after
+/// the queried height. If there is no entry at a height not greater than the queried one
+/// in this checkpoint, the balance is the one of the previous checkpoint, if any.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ArchivedBalanceProof {
+    /// Length of the list of the wallet checkpoints.
+    pub checkpoints_len: u64,
+    /// Merkle root of the list of the wallet checkpoints.
+    pub checkpoints_hash: Hash,
+    /// Index of the checkpoint into which the entries are compacted.
+    pub checkpoint: u64,
+    /// Proof of the checkpoint and the previous one, if any, in the list of the wallet
+    /// checkpoints.
+    pub checkpoints: ListProof<HistoryCheckpoint>,
+    /// Proof of the compacted entries around the queried height.
+    pub entries: ListProof<HistoryEntry>,
+}
+
+/// Describes the query parameters for the `archived_history` endpoint.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct ArchivedHistoryQuery {
+    /// Public key of the queried wallet.
+    pub pub_key: PublicKey,
+    /// Index of the checkpoint.
+    pub checkpoint: u64,
+}
+
+/// History entries of the wallet compacted into the specific checkpoint.
+///
+/// The first entry of the wallet history is the checkpoint entry, its `tx_hash` is
+/// `history::checkpoints_commitment` of `checkpoints_len` and `checkpoints_hash`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ArchivedHistoryInfo {
+    /// Proof of the last block.
+    pub block_proof: BlockProof,
+    /// Proof of the appropriate wallet.
+    pub wallet_proof: WalletProof,
+    /// Proof of the checkpoint entry of the wallet history.
+    pub history_head: ListProof<HistoryEntry>,
+    /// Length of the list of the wallet checkpoints.
+    pub checkpoints_len: u64,
+    /// Merkle root of the list of the wallet checkpoints.
+    pub checkpoints_hash: Hash,
+    /// Proof of the checkpoint in the list of the wallet checkpoints.
+    pub checkpoint: ListProof<HistoryCheckpoint>,
+    /// Proof of the list of the compacted entries.
+    pub entries: ListProof<HistoryEntry>,
+    /// List of the transactions of above entries.
+    pub transactions: Vec<TransactionMessage>,
+}
+
+/// Describes the query parameters for the `block_statistics` endpoint.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct StatisticsQuery {
+    /// Height of the first block in the range.
+    pub from: Height,
+    /// Height following the last block in the range.
+    pub to: Height,
+}
+
+/// Statistics of the blocks in the specific range of heights.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StatisticsInfo {
+    /// Proof of the last block.
+    pub block_proof: BlockProof,
+    /// Proof of the whole database table.
+    pub to_table: MapProof<Hash, Hash>,
+    /// Length of the list of the block statistics.
+    pub statistics_len: u64,
+    /// Proof of the statistics of the blocks in the range, if there are any.
+    pub statistics: Option<ListProof<BlockStatistics>>,
+}
+
+/// Describes the query parameters for the `pending_approvals` endpoint.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct PendingApprovalsQuery {
+    /// Public key of the approver.
+    pub approver: PublicKey,
+}
+
+/// Transfer awaiting the approval.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PendingApproval {
+    /// `Hash` of the transfer transaction.
+    pub transfer_tx_hash: Hash,
+    /// `PublicKey` of the sender's wallet.
+    pub from: PublicKey,
+    /// `PublicKey` of the receiver's wallet.
+    pub to: PublicKey,
+    /// Amount of the transfer.
+    pub amount: u64,
+    /// Number of blocks committed since the block with the transfer.
+    pub age: u64,
+    /// Proof of the transfer in the table of the pending transfers.
+    pub proof: MapProof<Hash, Transfer>,
+}
+
+/// Transfers awaiting the approval of the specific approver.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PendingApprovalsInfo {
+    /// Proof of the last block.
+    pub block_proof: BlockProof,
+    /// Proof of the table of the pending transfers.
+    pub to_table: MapProof<Hash, Hash>,
+    /// Transfers awaiting the approval, from the oldest one.
+    pub approvals: Vec<PendingApproval>,
+}
+
+/// Describes the query parameters for the `events` endpoint.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct EventsQuery {
+    /// Public key of the subscriber.
+    pub pub_key: PublicKey,
+    /// Index of the first event of the page.
+    #[serde(default)]
+    pub from: u64,
+    /// Maximum number of the events in the page, `MAX_EVENTS_PAGE_LEN` if not specified.
+    pub limit: Option<u64>,
+}
+
+/// Page of the event log of the specific subscriber.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EventsPage {
+    /// Events of the page.
+    pub events: Vec<WalletEvent>,
+    /// Index of the oldest event kept in the log, the events before it are pruned.
+    pub first: u64,
+    /// Number of the requested events which are pruned, i.e. skipped from the requested
+    /// index to `first`. The subscriber missed these events unless it is zero.
+    pub pruned: u64,
+    /// Index of the event from which the next page starts.
+    pub next: u64,
+}
+
+/// Describes the body of the `transactions/unsigned` endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnsignedTransactionQuery {
+    /// Public key of the author who is going to sign the transaction.
+    pub author: PublicKey,
+    /// Transaction parameters, e.g. `{ "Transfer": { "from": ..., "to": ... } }`.
+    pub transaction: WalletTransactions,
+}
+
+/// Unsigned transaction message.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UnsignedTransaction {
+    /// Hex-encoded bytes of the message which should be signed by the author.
+    pub message: String,
+    /// `Hash` of the message bytes. The signature covers the bytes themselves,
+    /// so the hash is only meant to check that the message was not altered.
+    pub message_hash: Hash,
+}
+
+/// Describes the body of the `transactions/signed` endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedTransactionQuery {
+    /// Hex-encoded message returned by the `transactions/unsigned` endpoint.
+    pub message: String,
+    /// Signature of the message made by its author.
+    pub signature: Signature,
+}
+
+/// Response of the `transactions/signed` endpoint.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SubmittedTransaction {
+    /// `Hash` of the broadcast transaction.
+    pub tx_hash: Hash,
+}
+
+/// Describes the body of the `transactions/simulate` endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SimulationQuery {
+    /// Signed transaction in the format of the explorer `transactions` endpoint.
+    Signed {
+        /// Hex-encoded signed transaction message.
+        tx_body: String,
+    },
+    /// Unsigned transaction in the format of the `transactions/unsigned` endpoint.
+    Unsigned(UnsignedTransactionQuery),
+}
+
+/// Error which the simulated transaction fails with.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SimulationError {
+    /// Code of the error, see the `errors` endpoint.
+    pub code: u8,
+    /// Name of the error.
+    pub name: String,
+    /// Description of the error.
+    pub description: String,
+}
+
+/// Change of the wallet made by the simulated transaction.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct WalletDelta {
+    /// `PublicKey` of the wallet.
+    pub pub_key: PublicKey,
+    /// Change of the balance of the wallet.
+    pub balance: i64,
+    /// Change of the retained amount of the wallet.
+    pub retained_amount: i64,
+}
+
+/// Result of the transaction simulation.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SimulationResult {
+    /// `Hash` under which the transaction was executed; for the unsigned transactions
+    /// it's the hash of the unsigned message.
+    pub tx_hash: Hash,
+    /// Error of the transaction, `None` if the transaction would succeed.
+    pub error: Option<SimulationError>,
+    /// Changes of the wallets affected by the transaction.
+    pub wallets: Vec<WalletDelta>,
+}
+
 /// Public service API description.
 #[derive(Debug, Clone, Copy)]
 pub struct PublicApi;
 
 impl PublicApi {
     /// Endpoint for getting a single wallet.
-    pub fn wallet_info(state: &ServiceApiState, query: WalletQuery) -> api::Result<WalletInfo> {
+    pub fn wallet_info(state: &ServiceApiState, query: WalletInfoQuery) -> api::Result<WalletInfo> {
         let snapshot = state.snapshot();
         let general_schema = blockchain::Schema::new(&snapshot);
         let currency_schema = Schema::new(&snapshot);
@@ -62,34 +662,14 @@ impl PublicApi {
             .block_and_precommits(Height(max_height))
             .unwrap();
 
-        let to_table: MapProof<Hash, Hash> =
-            general_schema.get_proof_to_service_table(CRYPTOCURRENCY_SERVICE_ID, 0);
-
-        let to_wallet: MapProof<PublicKey, Wallet> =
-            currency_schema.wallets().get_proof(query.pub_key);
-
-        let wallet_proof = WalletProof {
-            to_table,
-            to_wallet,
-        };
+        let wallet_proof = wallet_proof(&general_schema, &currency_schema, &query.pub_key);
 
-        let wallet = currency_schema.wallet(&query.pub_key);
+        let wallet = currency_schema.wallet(&query.pub_key).filter(|_| !query.skip_history);
 
         let explorer = BlockchainExplorer::new(state.blockchain());
 
-        let wallet_history = wallet.map(|_| {
-            let history = currency_schema.wallet_history(&query.pub_key);
-            let proof = history.get_range_proof(0, history.len());
-
-            let transactions = history
-                .iter()
-                .map(|record| explorer.transaction_without_proof(&record).unwrap())
-                .collect::<Vec<_>>();
-
-            WalletHistory {
-                proof,
-                transactions,
-            }
+        let wallet_history = wallet.map(|wallet| {
+            wallet_history_page(&explorer, &currency_schema, &query.pub_key, 0, wallet.history_len)
         });
 
         Ok(WalletInfo {
@@ -99,10 +679,1132 @@ impl PublicApi {
         })
     }
 
-    /// Wires the above endpoint to public scope of the given `ServiceApiBuilder`.
-    pub fn wire(builder: &mut ServiceApiBuilder) {
-        builder
-            .public_scope()
-            .endpoint("v1/wallets/info", Self::wallet_info);
+    /// Endpoint for listing the wallets matching the given filters.
+    ///
+    /// At most `MAX_WALLETS_SCAN` wallets are examined, so a page may contain less wallets
+    /// than requested while there are further ones.
+    pub fn wallets(state: &ServiceApiState, query: WalletsQuery) -> api::Result<WalletsPage> {
+        let snapshot = state.snapshot();
+        let general_schema = blockchain::Schema::new(&snapshot);
+        let currency_schema = Schema::new(&snapshot);
+
+        let block_proof = latest_block_proof(&general_schema);
+        let to_table: MapProof<Hash, Hash> =
+            general_schema.get_proof_to_service_table(CRYPTOCURRENCY_SERVICE_ID, WALLETS_TABLE);
+
+        let limit = query.limit.unwrap_or(MAX_WALLETS_PAGE_LEN).min(MAX_WALLETS_PAGE_LEN);
+        let name_prefix = query.name_prefix.as_ref().map(|prefix| wallet::normalize_name(prefix));
+        let matches = |wallet: &Wallet| {
+            name_prefix
+                .as_ref()
+                .map_or(true, |prefix| wallet::normalize_name(&wallet.name).starts_with(prefix))
+                && query.min_balance.map_or(true, |min_balance| wallet.balance >= min_balance)
+                && (!query.with_retained || wallet.retained_amount > 0)
+        };
+
+        let index = currency_schema.wallets();
+        let entries = match query.from {
+            Some(from) => index.iter_from(&from),
+            None => index.iter(),
+        };
+        let mut wallets = Vec::new();
+        let mut next = None;
+        for (scanned, (pub_key, wallet)) in entries.enumerate() {
+            if wallets.len() as u64 == limit || scanned as u64 == MAX_WALLETS_SCAN {
+                next = Some(pub_key);
+                break;
+            }
+            if matches(&wallet) {
+                wallets.push(wallet);
+            }
+        }
+
+        let proof = if query.with_proof {
+            Some(index.get_multiproof(wallets.iter().map(|wallet| wallet.pub_key)))
+        } else {
+            None
+        };
+
+        Ok(WalletsPage {
+            block_proof,
+            to_table,
+            wallets,
+            proof,
+            next,
+        })
     }
-}
\ No newline at end of file
+
+    /// Endpoint for getting many wallets under a single block proof.
+    ///
+    /// The keys are passed in the request body, since their list is too long for a query string.
+    pub fn wallets_batch(
+        state: &ServiceApiState,
+        query: WalletsBatchQuery,
+    ) -> api::Result<WalletsBatchInfo> {
+        if query.pub_keys.len() as u64 > MAX_WALLETS_BATCH_LEN {
+            Err(api::Error::BadRequest(format!(
+                "Batch can't contain more than {} wallets",
+                MAX_WALLETS_BATCH_LEN
+            )))?;
+        }
+
+        let snapshot = state.snapshot();
+        let general_schema = blockchain::Schema::new(&snapshot);
+        let currency_schema = Schema::new(&snapshot);
+
+        let block_proof = latest_block_proof(&general_schema);
+        let to_table: MapProof<Hash, Hash> =
+            general_schema.get_proof_to_service_table(CRYPTOCURRENCY_SERVICE_ID, WALLETS_TABLE);
+        let pub_keys = query.pub_keys.into_iter().collect::<BTreeSet<_>>();
+        let to_wallets = currency_schema.wallets().get_multiproof(pub_keys);
+
+        Ok(WalletsBatchInfo {
+            block_proof,
+            to_table,
+            to_wallets,
+        })
+    }
+
+    /// Endpoint for listing the wallets ordered by balance, from the richest one.
+    pub fn richlist(state: &ServiceApiState, query: RichlistQuery) -> api::Result<RichlistPage> {
+        let from = match (query.from_balance, query.from) {
+            (Some(balance), Some(pub_key)) => Some(wallet::balance_key(balance, &pub_key)),
+            (None, None) => None,
+            _ => Err(api::Error::BadRequest(
+                "`from_balance` and `from` must be passed together".to_owned(),
+            ))?,
+        };
+
+        let snapshot = state.snapshot();
+        let general_schema = blockchain::Schema::new(&snapshot);
+        let currency_schema = Schema::new(&snapshot);
+
+        let block_proof = latest_block_proof(&general_schema);
+        let to_table: MapProof<Hash, Hash> =
+            general_schema.get_proof_to_service_table(CRYPTOCURRENCY_SERVICE_ID, WALLETS_TABLE);
+
+        let limit = query.limit.unwrap_or(MAX_WALLETS_PAGE_LEN).min(MAX_WALLETS_PAGE_LEN);
+        let index = currency_schema.wallets();
+        let by_balance = currency_schema.wallets_by_balance();
+        let entries = match from {
+            Some(ref from) => by_balance.iter_from(from),
+            None => by_balance.iter(),
+        };
+        let mut wallets = Vec::new();
+        let mut next = None;
+        for (_, pub_key) in entries {
+            let wallet = index.get(&pub_key).unwrap();
+            if wallets.len() as u64 == limit {
+                next = Some(RichlistCursor {
+                    balance: wallet.balance,
+                    pub_key,
+                });
+                break;
+            }
+            wallets.push(wallet);
+        }
+
+        let proof = if query.with_proof {
+            Some(index.get_multiproof(wallets.iter().map(|wallet| wallet.pub_key)))
+        } else {
+            None
+        };
+
+        Ok(RichlistPage {
+            block_proof,
+            to_table,
+            wallets,
+            proof,
+            next,
+        })
+    }
+
+    /// Endpoint for getting a page of the history of a single wallet.
+    pub fn wallet_history(
+        state: &ServiceApiState,
+        query: WalletHistoryQuery,
+    ) -> api::Result<WalletHistoryInfo> {
+        let snapshot = state.snapshot();
+        let general_schema = blockchain::Schema::new(&snapshot);
+        let currency_schema = Schema::new(&snapshot);
+
+        let block_proof = latest_block_proof(&general_schema);
+        let wallet_proof = wallet_proof(&general_schema, &currency_schema, &query.pub_key);
+
+        let limit = query.limit.unwrap_or(MAX_HISTORY_PAGE_LEN).min(MAX_HISTORY_PAGE_LEN);
+        let history_len = currency_schema.wallet_history(&query.pub_key).len();
+        let to = query.offset.saturating_add(limit).min(history_len);
+
+        let explorer = BlockchainExplorer::new(state.blockchain());
+        let history = if query.offset < to {
+            Some(wallet_history_page(&explorer, &currency_schema, &query.pub_key, query.offset, to))
+        } else {
+            None
+        };
+
+        Ok(WalletHistoryInfo {
+            block_proof,
+            wallet_proof,
+            history,
+        })
+    }
+
+    /// Endpoint for getting the balance of a single wallet at the given height.
+    pub fn balance_at(state: &ServiceApiState, query: BalanceAtQuery) -> api::Result<BalanceAtInfo> {
+        let snapshot = state.snapshot();
+        let general_schema = blockchain::Schema::new(&snapshot);
+        let currency_schema = Schema::new(&snapshot);
+
+        let block_proof = latest_block_proof(&general_schema);
+        let height_block_proof = general_schema
+            .block_and_precommits(query.height)
+            .ok_or_else(|| api::Error::NotFound("Block at the given height not found".to_owned()))?;
+
+        let wallet_proof = wallet_proof(&general_schema, &currency_schema, &query.pub_key);
+
+        let history = currency_schema.wallet_history(&query.pub_key);
+        let (entries, archived, balance) = if history.len() > 0 {
+            let position = currency_schema.wallet_history_len_at(&query.pub_key, query.height);
+            if position == 0 && history.get(0).unwrap().is_checkpoint() {
+                let (archived, balance) =
+                    archived_balance_proof(&currency_schema, &query.pub_key, query.height);
+                (Some(history.get_proof(0)), Some(archived), balance)
+            } else {
+                let from = position.saturating_sub(1);
+                let to = (position + 1).min(history.len());
+                let balance = position
+                    .checked_sub(1)
+                    .map(|index| history.get(index).unwrap().balance);
+                (Some(history.get_range_proof(from, to)), None, balance)
+            }
+        } else {
+            (None, None, None)
+        };
+
+        Ok(BalanceAtInfo {
+            block_proof,
+            wallet_proof,
+            height_block_proof,
+            entries,
+            archived,
+            balance,
+        })
+    }
+
+    /// Endpoint for getting the history entries of a single wallet compacted into
+    /// the given checkpoint.
+    pub fn archived_history(
+        state: &ServiceApiState,
+        query: ArchivedHistoryQuery,
+    ) -> api::Result<ArchivedHistoryInfo> {
+        let snapshot = state.snapshot();
+        let general_schema = blockchain::Schema::new(&snapshot);
+        let currency_schema = Schema::new(&snapshot);
+
+        let checkpoints = currency_schema.wallet_checkpoints(&query.pub_key);
+        if query.checkpoint >= checkpoints.len() {
+            Err(api::Error::NotFound("Checkpoint not found".to_owned()))?;
+        }
+
+        let block_proof = latest_block_proof(&general_schema);
+        let wallet_proof = wallet_proof(&general_schema, &currency_schema, &query.pub_key);
+
+        let history_head = currency_schema.wallet_history(&query.pub_key).get_proof(0);
+        let checkpoint = checkpoints.get_proof(query.checkpoint);
+
+        let archive = currency_schema.archived_history(&query.pub_key, query.checkpoint);
+        let entries = archive.get_range_proof(0, archive.len());
+        let explorer = BlockchainExplorer::new(state.blockchain());
+        let transactions = archive
+            .iter()
+            .map(|entry| explorer.transaction_without_proof(&entry.tx_hash).unwrap())
+            .collect::<Vec<_>>();
+
+        Ok(ArchivedHistoryInfo {
+            block_proof,
+            wallet_proof,
+            history_head,
+            checkpoints_len: checkpoints.len(),
+            checkpoints_hash: checkpoints.merkle_root(),
+            checkpoint,
+            entries,
+            transactions,
+        })
+    }
+
+    /// Endpoint for getting the sender filter of a single wallet.
+    pub fn sender_filter(
+        state: &ServiceApiState,
+        query: WalletQuery,
+    ) -> api::Result<SenderFilterInfo> {
+        let snapshot = state.snapshot();
+        let general_schema = blockchain::Schema::new(&snapshot);
+        let currency_schema = Schema::new(&snapshot);
+
+        let block_proof = latest_block_proof(&general_schema);
+
+        let to_table: MapProof<Hash, Hash> = general_schema
+            .get_proof_to_service_table(CRYPTOCURRENCY_SERVICE_ID, SENDER_FILTERS_TABLE);
+
+        let to_filter: MapProof<PublicKey, SenderFilter> =
+            currency_schema.sender_filters().get_proof(query.pub_key);
+
+        let filter_proof = SenderFilterProof {
+            to_table,
+            to_filter,
+        };
+
+        let sender_list = currency_schema.sender_filter(&query.pub_key).map(|_| {
+            let list = currency_schema.sender_list(&query.pub_key);
+            list.get_multiproof(list.keys())
+        });
+
+        Ok(SenderFilterInfo {
+            block_proof,
+            filter_proof,
+            sender_list,
+        })
+    }
+
+    /// Endpoint for resolving a wallet name to the public key.
+    pub fn resolve_name(state: &ServiceApiState, query: NameQuery) -> api::Result<NameInfo> {
+        let snapshot = state.snapshot();
+        let general_schema = blockchain::Schema::new(&snapshot);
+        let currency_schema = Schema::new(&snapshot);
+
+        let block_proof = latest_block_proof(&general_schema);
+        let name_proof = name_proof(&general_schema, &currency_schema, &query.name);
+
+        Ok(NameInfo {
+            block_proof,
+            name_proof,
+        })
+    }
+
+    /// Endpoint for resolving a public key to the wallet name.
+    pub fn reverse_resolve_name(
+        state: &ServiceApiState,
+        query: WalletQuery,
+    ) -> api::Result<ReverseNameInfo> {
+        let snapshot = state.snapshot();
+        let general_schema = blockchain::Schema::new(&snapshot);
+        let currency_schema = Schema::new(&snapshot);
+
+        let block_proof = latest_block_proof(&general_schema);
+
+        let wallet_proof = WalletProof {
+            to_table: general_schema
+                .get_proof_to_service_table(CRYPTOCURRENCY_SERVICE_ID, WALLETS_TABLE),
+            to_wallet: currency_schema.wallets().get_proof(query.pub_key),
+        };
+
+        let name_proof = currency_schema
+            .wallet(&query.pub_key)
+            .map(|wallet| name_proof(&general_schema, &currency_schema, &wallet.name));
+
+        Ok(ReverseNameInfo {
+            block_proof,
+            wallet_proof,
+            name_proof,
+        })
+    }
+
+    /// Endpoint for getting a single invoice.
+    pub fn invoice_info(state: &ServiceApiState, query: InvoiceQuery) -> api::Result<InvoiceInfo> {
+        let snapshot = state.snapshot();
+        let general_schema = blockchain::Schema::new(&snapshot);
+        let currency_schema = Schema::new(&snapshot);
+
+        let block_proof = latest_block_proof(&general_schema);
+
+        let invoice_proof = InvoiceProof {
+            to_table: general_schema
+                .get_proof_to_service_table(CRYPTOCURRENCY_SERVICE_ID, INVOICES_TABLE),
+            to_invoice: currency_schema.invoices().get_proof(query.id),
+        };
+
+        Ok(InvoiceInfo {
+            block_proof,
+            invoice_proof,
+        })
+    }
+
+    /// Endpoint for getting a single settled transfer with its refunds.
+    pub fn settled_transfer(
+        state: &ServiceApiState,
+        query: TransferQuery,
+    ) -> api::Result<SettledTransferInfo> {
+        let snapshot = state.snapshot();
+        let general_schema = blockchain::Schema::new(&snapshot);
+        let currency_schema = Schema::new(&snapshot);
+
+        let block_proof = latest_block_proof(&general_schema);
+
+        let transfer_proof = SettledTransferProof {
+            to_table: general_schema
+                .get_proof_to_service_table(CRYPTOCURRENCY_SERVICE_ID, SETTLED_TRANSFERS_TABLE),
+            to_transfer: currency_schema.settled_transfers().get_proof(query.hash),
+        };
+
+        let refunds = currency_schema
+            .settled_transfer(&query.hash)
+            .filter(|settled| settled.refunds_len > 0)
+            .map(|_| {
+                let refunds = currency_schema.transfer_refunds(&query.hash);
+                refunds.get_range_proof(0, refunds.len())
+            });
+
+        Ok(SettledTransferInfo {
+            block_proof,
+            transfer_proof,
+            refunds,
+        })
+    }
+
+    /// Endpoint for getting the status of a single transfer.
+    pub fn transfer_status(
+        state: &ServiceApiState,
+        query: TransferQuery,
+    ) -> api::Result<TransferStatusInfo> {
+        let snapshot = state.snapshot();
+        let general_schema = blockchain::Schema::new(&snapshot);
+        let currency_schema = Schema::new(&snapshot);
+
+        let block_proof = latest_block_proof(&general_schema);
+
+        let status_proof = TransferStatusProof {
+            to_table: general_schema
+                .get_proof_to_service_table(CRYPTOCURRENCY_SERVICE_ID, TRANSFER_STATUSES_TABLE),
+            to_status: currency_schema.transfer_statuses().get_proof(query.hash),
+        };
+
+        Ok(TransferStatusInfo {
+            block_proof,
+            status_proof,
+        })
+    }
+
+    /// Endpoint for getting the attestation of the condition by the oracle.
+    pub fn attestation(
+        state: &ServiceApiState,
+        query: AttestationQuery,
+    ) -> api::Result<AttestationInfo> {
+        let snapshot = state.snapshot();
+        let general_schema = blockchain::Schema::new(&snapshot);
+        let currency_schema = Schema::new(&snapshot);
+
+        let block_proof = latest_block_proof(&general_schema);
+
+        let key = oracle::attestation_key(&query.oracle, &query.condition);
+        let attestation_proof = AttestationProof {
+            to_table: general_schema
+                .get_proof_to_service_table(CRYPTOCURRENCY_SERVICE_ID, ATTESTATIONS_TABLE),
+            to_attestation: currency_schema.attestations().get_proof(key),
+        };
+
+        Ok(AttestationInfo {
+            block_proof,
+            attestation_proof,
+        })
+    }
+
+    /// Endpoint for getting a single registered approver.
+    pub fn approver_info(state: &ServiceApiState, query: WalletQuery) -> api::Result<ApproverInfo> {
+        let snapshot = state.snapshot();
+        let general_schema = blockchain::Schema::new(&snapshot);
+        let currency_schema = Schema::new(&snapshot);
+
+        let block_proof = latest_block_proof(&general_schema);
+
+        let approver_proof = ApproverProof {
+            to_table: general_schema
+                .get_proof_to_service_table(CRYPTOCURRENCY_SERVICE_ID, APPROVERS_TABLE),
+            to_approver: currency_schema.approvers().get_proof(query.pub_key),
+        };
+
+        Ok(ApproverInfo {
+            block_proof,
+            approver_proof,
+        })
+    }
+
+    /// Endpoint for getting the pending transfers of a single party.
+    pub fn pending_transfers(
+        state: &ServiceApiState,
+        query: PendingTransfersQuery,
+    ) -> api::Result<PendingTransfersInfo> {
+        let snapshot = state.snapshot();
+        let general_schema = blockchain::Schema::new(&snapshot);
+        let currency_schema = Schema::new(&snapshot);
+
+        let block_proof = latest_block_proof(&general_schema);
+
+        let to_table = general_schema
+            .get_proof_to_service_table(CRYPTOCURRENCY_SERVICE_ID, query.party.roots_table());
+
+        let roots = currency_schema.pending_transfers_roots(query.party);
+        let to_root = roots.get_proof(query.pub_key);
+
+        let transfers = roots.get(&query.pub_key).map(|_| {
+            let index = currency_schema.pending_transfers(query.party, &query.pub_key);
+            index.get_multiproof(index.keys())
+        });
+
+        Ok(PendingTransfersInfo {
+            block_proof,
+            to_table,
+            to_root,
+            transfers,
+        })
+    }
+
+    /// Endpoint for getting the transfers awaiting the approval of a single approver.
+    ///
+    /// Conditional transfers are settled by the attestations and are not listed.
+    pub fn pending_approvals(
+        state: &ServiceApiState,
+        query: PendingApprovalsQuery,
+    ) -> api::Result<PendingApprovalsInfo> {
+        let snapshot = state.snapshot();
+        let general_schema = blockchain::Schema::new(&snapshot);
+        let currency_schema = Schema::new(&snapshot);
+
+        let block_proof = latest_block_proof(&general_schema);
+        let to_table: MapProof<Hash, Hash> =
+            general_schema.get_proof_to_service_table(CRYPTOCURRENCY_SERVICE_ID, TRANSFERS_TABLE);
+
+        let max_height = general_schema.block_hashes_by_height().len() - 1;
+        let locations = general_schema.transactions_locations();
+        let transfers = currency_schema.transfers();
+        let mut approvals = currency_schema
+            .pending_transfers(TransferParty::Approver, &query.approver)
+            .iter()
+            .filter(|(_, transfer)| !transfer.is_conditional())
+            .map(|(hash, transfer)| {
+                let height = locations.get(&hash).unwrap().block_height();
+                PendingApproval {
+                    transfer_tx_hash: hash,
+                    from: transfer.from,
+                    to: transfer.to,
+                    amount: transfer.amount,
+                    age: max_height - height.0,
+                    proof: transfers.get_proof(hash),
+                }
+            })
+            .collect::<Vec<_>>();
+        approvals.sort_by(|a, b| b.age.cmp(&a.age));
+
+        Ok(PendingApprovalsInfo {
+            block_proof,
+            to_table,
+            approvals,
+        })
+    }
+
+    /// Endpoint for getting a page of the event log of a single subscriber.
+    ///
+    /// Events are added when the block with the transactions causing them is committed.
+    /// Subscribers follow their event logs by requesting the page from the `next`
+    /// index of the previous one after each block. The log keeps the last
+    /// `MAX_EVENTS_LOG_LEN` events only, so the page starts from the `first` kept event
+    /// if the requested ones are pruned, and reports the number of the `pruned` ones.
+    ///
+    /// There is no long-poll: the API handlers are synchronous, so a waiting request
+    /// would hold an API worker until the next block.
+    pub fn events(state: &ServiceApiState, query: EventsQuery) -> api::Result<EventsPage> {
+        let snapshot = state.snapshot();
+        let currency_schema = Schema::new(&snapshot);
+
+        let first = currency_schema.first_wallet_event(&query.pub_key);
+        let from = query.from.max(first);
+        let limit = query.limit.unwrap_or(MAX_EVENTS_PAGE_LEN).min(MAX_EVENTS_PAGE_LEN);
+        let events = currency_schema
+            .wallet_events(&query.pub_key)
+            .iter_from(&from)
+            .take(limit as usize)
+            .map(|(_, event)| event)
+            .collect::<Vec<_>>();
+        let pruned = from - query.from;
+        let next = from + events.len() as u64;
+
+        Ok(EventsPage {
+            events,
+            first,
+            pruned,
+            next,
+        })
+    }
+
+    /// Endpoint for building the unsigned message of any wallet transaction.
+    ///
+    /// Lets clients sign transactions without reimplementing the protobuf encoding.
+    pub fn unsigned_transaction(
+        _state: &ServiceApiState,
+        query: UnsignedTransactionQuery,
+    ) -> api::Result<UnsignedTransaction> {
+        let message = unsigned_message(&query.author, query.transaction);
+        Ok(UnsignedTransaction {
+            message_hash: crypto::hash(&message),
+            message: encode_hex(&message),
+        })
+    }
+
+    /// Endpoint for submitting a message of the `transactions/unsigned` endpoint
+    /// along with its detached signature.
+    pub fn signed_transaction(
+        state: &ServiceApiState,
+        query: SignedTransactionQuery,
+    ) -> api::Result<SubmittedTransaction> {
+        let mut buffer = decode_hex(&query.message)
+            .ok_or_else(|| api::Error::BadRequest("Message is not a valid hex string".to_owned()))?;
+        buffer.extend_from_slice(query.signature.as_ref());
+
+        let tx = parse_transaction(buffer)?;
+        WalletTransactions::tx_from_raw(tx.payload().clone())
+            .map_err(|e| api::Error::BadRequest(format!("Invalid transaction: {}", e)))?;
+
+        let tx_hash = tx.hash();
+        state
+            .sender()
+            .broadcast_transaction(tx)
+            .map_err(api::Error::InternalError)?;
+        Ok(SubmittedTransaction { tx_hash })
+    }
+
+    /// Endpoint for executing the transaction against a fork of the current state
+    /// without committing it.
+    ///
+    /// Panic of the upgrade of the stored records or of the transaction execution
+    /// is reported as an internal error.
+    pub fn simulate_transaction(
+        state: &ServiceApiState,
+        query: SimulationQuery,
+    ) -> api::Result<SimulationResult> {
+        let (author, tx_hash, transaction) = match query {
+            SimulationQuery::Signed { tx_body } => {
+                let buffer = decode_hex(&tx_body).ok_or_else(|| {
+                    api::Error::BadRequest("Transaction is not a valid hex string".to_owned())
+                })?;
+                let tx = parse_transaction(buffer)?;
+                let transaction = WalletTransactions::tx_from_raw(tx.payload().clone())
+                    .map_err(|e| api::Error::BadRequest(format!("Invalid transaction: {}", e)))?;
+                (tx.author(), tx.hash(), transaction)
+            }
+            SimulationQuery::Unsigned(query) => {
+                let message = unsigned_message(&query.author, query.transaction.clone());
+                (query.author, crypto::hash(&message), query.transaction)
+            }
+        };
+
+        let amounts = |wallet: Option<Wallet>| {
+            wallet.map_or((0, 0), |w| (w.balance as i64, w.retained_amount as i64))
+        };
+
+        // The state before the transaction is read from the fork it is executed on,
+        // after upgrading the stored records as the transactions do. The upgrade panics
+        // on the records written by a newer version, so it runs under the same guard.
+        let mut fork = state.blockchain().fork();
+        let (before, result) = panic::catch_unwind(AssertUnwindSafe(|| {
+            let before: Vec<(PublicKey, (i64, i64))> = {
+                let mut currency_schema = Schema::new(&mut fork);
+                currency_schema.migrate();
+                affected_wallets(&currency_schema, &author, &transaction)
+                    .into_iter()
+                    .map(|pub_key| (pub_key, amounts(currency_schema.wallet(&pub_key))))
+                    .collect()
+            };
+            let context = ExecutionContext::new(&mut fork, author, tx_hash);
+            (before, Execute::execute(&transaction, context))
+        }))
+        .map_err(|payload| {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_default();
+            api::Error::InternalError(format_err!("Transaction simulation panicked: {}", message))
+        })?;
+        let error = result.err().map(|e| {
+            let name = transactions::error_catalogue()
+                .into_iter()
+                .find(|info| info.code == e.code)
+                .map(|info| info.name)
+                .unwrap_or_default();
+            SimulationError {
+                code: e.code,
+                name,
+                description: e.description.unwrap_or_default(),
+            }
+        });
+
+        let simulated_schema = Schema::new(&mut fork);
+        let wallets = before
+            .into_iter()
+            .filter_map(|(pub_key, (balance, retained))| {
+                let (new_balance, new_retained) = amounts(simulated_schema.wallet(&pub_key));
+                if (balance, retained) == (new_balance, new_retained) {
+                    return None;
+                }
+                Some(WalletDelta {
+                    pub_key,
+                    balance: new_balance - balance,
+                    retained_amount: new_retained - retained,
+                })
+            })
+            .collect();
+
+        Ok(SimulationResult { tx_hash, error, wallets })
+    }
+
+    /// Endpoint for getting the statistics of the blocks in the given range of heights.
+    pub fn block_statistics(
+        state: &ServiceApiState,
+        query: StatisticsQuery,
+    ) -> api::Result<StatisticsInfo> {
+        if query.to <= query.from || query.to.0 - query.from.0 > MAX_STATISTICS_RANGE {
+            Err(api::Error::BadRequest(format!(
+                "Range of heights should be non-empty and contain at most {} blocks",
+                MAX_STATISTICS_RANGE
+            )))?;
+        }
+
+        let snapshot = state.snapshot();
+        let general_schema = blockchain::Schema::new(&snapshot);
+        let currency_schema = Schema::new(&snapshot);
+
+        let block_proof = latest_block_proof(&general_schema);
+        let to_table: MapProof<Hash, Hash> = general_schema
+            .get_proof_to_service_table(CRYPTOCURRENCY_SERVICE_ID, BLOCK_STATISTICS_TABLE);
+
+        let index = currency_schema.block_statistics();
+        let first_height = index.get(0).map_or(0, |statistics| statistics.height);
+        let from = query.from.0.max(first_height) - first_height;
+        let to = query.to.0.saturating_sub(first_height).min(index.len());
+        let statistics = if from < to {
+            Some(index.get_range_proof(from, to))
+        } else {
+            None
+        };
+
+        Ok(StatisticsInfo {
+            block_proof,
+            to_table,
+            statistics_len: index.len(),
+            statistics,
+        })
+    }
+
+    /// Endpoint for getting the catalogue of the errors emitted by the service transactions.
+    pub fn errors(_state: &ServiceApiState, _query: ()) -> api::Result<Vec<ErrorInfo>> {
+        Ok(transactions::error_catalogue())
+    }
+
+    /// Wires the above endpoints to public scope of the given `ServiceApiBuilder`.
+    pub fn wire(builder: &mut ServiceApiBuilder) {
+        builder
+            .public_scope()
+            .endpoint("v1/wallets", Self::wallets)
+            .endpoint("v1/wallets/richlist", Self::richlist)
+            .endpoint_mut("v1/wallets/batch", Self::wallets_batch)
+            .endpoint("v1/wallets/info", Self::wallet_info)
+            .endpoint("v1/wallets/history", Self::wallet_history)
+            .endpoint("v1/wallets/balance_at", Self::balance_at)
+            .endpoint("v1/wallets/archived_history", Self::archived_history)
+            .endpoint("v1/wallets/sender_filter", Self::sender_filter)
+            .endpoint("v1/names/resolve", Self::resolve_name)
+            .endpoint("v1/names/reverse", Self::reverse_resolve_name)
+            .endpoint("v1/invoices/info", Self::invoice_info)
+            .endpoint("v1/transfers/settled", Self::settled_transfer)
+            .endpoint("v1/transfers/status", Self::transfer_status)
+            .endpoint("v1/oracles/attestation", Self::attestation)
+            .endpoint("v1/approvers/info", Self::approver_info)
+            .endpoint("v1/transfers/pending", Self::pending_transfers)
+            .endpoint("v1/approvals/pending", Self::pending_approvals)
+            .endpoint("v1/events", Self::events)
+            .endpoint("v1/statistics/blocks", Self::block_statistics)
+            .endpoint("v1/errors", Self::errors)
+            .endpoint_mut("v1/transactions/unsigned", Self::unsigned_transaction)
+            .endpoint_mut("v1/transactions/signed", Self::signed_transaction)
+            .endpoint_mut("v1/transactions/simulate", Self::simulate_transaction);
+    }
+}
+
+/// Configuration of the service.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ServiceConfig {
+    /// Identifier of the service.
+    pub service_id: u16,
+    /// Name of the service.
+    pub service_name: String,
+    /// Initial balance of the wallet.
+    pub initial_balance: u64,
+    /// Version of the layout of the stored records.
+    pub schema_version: u32,
+    /// Version of the layout of the records written by this node.
+    pub supported_schema_version: u32,
+    /// Minimum stake of the approver.
+    pub min_approver_stake: u64,
+    /// Length of the wallet history at which it is compacted into a checkpoint.
+    pub history_compaction_threshold: u64,
+    /// Maximum number of the entries in the page of the wallet history.
+    pub max_history_page_len: u64,
+    /// Maximum number of the wallets in the page of the wallet listing.
+    pub max_wallets_page_len: u64,
+    /// Maximum number of the wallets in one batch query.
+    pub max_wallets_batch_len: u64,
+    /// Maximum number of blocks in the range of the statistics query.
+    pub max_statistics_range: u64,
+}
+
+/// Result of the consistency check of the retained amounts against the pending transfers.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConsistencyReport {
+    /// Total retained amount of the wallets.
+    pub total_retained: u64,
+    /// Number of the pending transfers.
+    pub pending_count: u64,
+    /// Total amount of the pending transfers.
+    pub pending_value: u64,
+    /// Number of the pending transfers recorded in the pending totals.
+    pub recorded_pending_count: u64,
+    /// Total amount of the pending transfers recorded in the pending totals.
+    pub recorded_pending_value: u64,
+    /// Wallets whose retained amount differs from the amount of their pending transfers.
+    pub mismatched_wallets: Vec<PublicKey>,
+    /// Wallets missing in the index of the wallets by balance or indexed with a stale balance.
+    pub unindexed_wallets: Vec<PublicKey>,
+    /// `true` if no inconsistencies were found.
+    pub consistent: bool,
+}
+
+/// Public keys of the service participants with special roles.
+///
+/// Issuance is not restricted to a role, so there is no list of issuers.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RolesInfo {
+    /// Service keys of the actual validators, allowed to slash approvers and reindex.
+    pub validators: Vec<PublicKey>,
+    /// Registered approvers.
+    pub approvers: Vec<Approver>,
+    /// Oracles which attested at least one condition.
+    pub oracles: Vec<PublicKey>,
+}
+
+/// Describes the query parameters for the `reindex` endpoint.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct ReindexQuery {
+    /// Seed of the `Reindex` transaction.
+    pub seed: u64,
+}
+
+/// Response of the `reindex` endpoint.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReindexResponse {
+    /// `Hash` of the broadcast `Reindex` transaction.
+    pub tx_hash: Hash,
+}
+
+/// Private service API description.
+#[derive(Debug, Clone, Copy)]
+pub struct PrivateApi;
+
+impl PrivateApi {
+    /// Endpoint for getting the configuration of the service.
+    pub fn config(state: &ServiceApiState, _query: ()) -> api::Result<ServiceConfig> {
+        let snapshot = state.snapshot();
+        let currency_schema = Schema::new(&snapshot);
+
+        Ok(ServiceConfig {
+            service_id: CRYPTOCURRENCY_SERVICE_ID,
+            service_name: SERVICE_NAME.to_owned(),
+            initial_balance: INITIAL_BALANCE,
+            schema_version: currency_schema.schema_version(),
+            supported_schema_version: SCHEMA_VERSION,
+            min_approver_stake: MIN_APPROVER_STAKE,
+            history_compaction_threshold: HISTORY_COMPACTION_THRESHOLD,
+            max_history_page_len: MAX_HISTORY_PAGE_LEN,
+            max_wallets_page_len: MAX_WALLETS_PAGE_LEN,
+            max_wallets_batch_len: MAX_WALLETS_BATCH_LEN,
+            max_statistics_range: MAX_STATISTICS_RANGE,
+        })
+    }
+
+    /// Endpoint for checking the retained amounts of the wallets against the pending transfers.
+    ///
+    /// Examines all the wallets and the pending transfers.
+    pub fn consistency(state: &ServiceApiState, _query: ()) -> api::Result<ConsistencyReport> {
+        let snapshot = state.snapshot();
+        let currency_schema = Schema::new(&snapshot);
+
+        let mut pending_by_sender = BTreeMap::new();
+        let (mut pending_count, mut pending_value) = (0, 0);
+        for transfer in currency_schema.transfers().values() {
+            *pending_by_sender.entry(transfer.from).or_insert(0) += transfer.amount;
+            pending_count += 1;
+            pending_value += transfer.amount;
+        }
+
+        let by_balance = currency_schema.wallets_by_balance();
+        let mut total_retained = 0;
+        let mut mismatched_wallets = Vec::new();
+        let mut unindexed_wallets = Vec::new();
+        for wallet in currency_schema.wallets().values() {
+            total_retained += wallet.retained_amount;
+            let pending = pending_by_sender.get(&wallet.pub_key).cloned().unwrap_or(0);
+            if wallet.retained_amount != pending {
+                mismatched_wallets.push(wallet.pub_key);
+            }
+            if !by_balance.contains(&wallet::balance_key(wallet.balance, &wallet.pub_key)) {
+                unindexed_wallets.push(wallet.pub_key);
+            }
+        }
+
+        let (recorded_pending_count, recorded_pending_value) = currency_schema.pending_totals();
+        let consistent = mismatched_wallets.is_empty()
+            && unindexed_wallets.is_empty()
+            && total_retained == pending_value
+            && (recorded_pending_count, recorded_pending_value) == (pending_count, pending_value);
+
+        Ok(ConsistencyReport {
+            total_retained,
+            pending_count,
+            pending_value,
+            recorded_pending_count,
+            recorded_pending_value,
+            mismatched_wallets,
+            unindexed_wallets,
+            consistent,
+        })
+    }
+
+    /// Endpoint for exporting the lists of the service participants with special roles.
+    pub fn roles(state: &ServiceApiState, _query: ()) -> api::Result<RolesInfo> {
+        let snapshot = state.snapshot();
+        let general_schema = blockchain::Schema::new(&snapshot);
+        let currency_schema = Schema::new(&snapshot);
+
+        let validators = general_schema
+            .actual_configuration()
+            .validator_keys
+            .iter()
+            .map(|keys| keys.service_key)
+            .collect();
+        let approvers = currency_schema.approvers().values().collect();
+        let oracles = currency_schema
+            .attestations()
+            .values()
+            .map(|attestation| attestation.oracle)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+
+        Ok(RolesInfo {
+            validators,
+            approvers,
+            oracles,
+        })
+    }
+
+    /// Endpoint for rebuilding the derived indexes of the service.
+    ///
+    /// Broadcasts the `Reindex` transaction signed with the service key of the node,
+    /// so the node should be a validator.
+    pub fn reindex(state: &ServiceApiState, query: ReindexQuery) -> api::Result<ReindexResponse> {
+        let tx = Reindex::sign(state.public_key(), query.seed, state.secret_key());
+        let tx_hash = tx.hash();
+        state
+            .sender()
+            .broadcast_transaction(tx)
+            .map_err(api::Error::InternalError)?;
+        Ok(ReindexResponse { tx_hash })
+    }
+
+    /// Wires the above endpoints to private scope of the given `ServiceApiBuilder`.
+    pub fn wire(builder: &mut ServiceApiBuilder) {
+        builder
+            .private_scope()
+            .endpoint("v1/config", Self::config)
+            .endpoint("v1/consistency", Self::consistency)
+            .endpoint("v1/roles", Self::roles)
+            .endpoint_mut("v1/reindex", Self::reindex);
+    }
+}
+
+/// Message class of the transactions.
+const TRANSACTION_CLASS: u8 = 0;
+/// Message type of the transactions.
+const TRANSACTION_TYPE: u8 = 0;
+
+/// Returns bytes of the transaction message of the given author without the trailing signature.
+///
+/// The layout is the one of Exonum transaction messages: author key, message class
+/// and type, service identifier, transaction identifier and protobuf payload.
+fn unsigned_message(author: &PublicKey, transaction: WalletTransactions) -> Vec<u8> {
+    let (transaction_id, payload) = ServiceTransaction::from(transaction).into_raw_parts();
+    let mut message = Vec::new();
+    message.extend_from_slice(author.as_ref());
+    message.extend_from_slice(&[TRANSACTION_CLASS, TRANSACTION_TYPE]);
+    message.extend_from_slice(&CRYPTOCURRENCY_SERVICE_ID.to_le_bytes());
+    message.extend_from_slice(&transaction_id.to_le_bytes());
+    message.extend_from_slice(&payload);
+    message
+}
+
+/// Parses the signed transaction of the service from the given message bytes.
+fn parse_transaction(buffer: Vec<u8>) -> api::Result<Signed<RawTransaction>> {
+    let message = Message::from_raw_buffer(buffer)
+        .map_err(|e| api::Error::BadRequest(format!("Invalid signed message: {}", e)))?;
+    let tx = RawTransaction::try_from(message)
+        .map_err(|_| api::Error::BadRequest("Message is not a transaction".to_owned()))?;
+    if tx.service_id() != CRYPTOCURRENCY_SERVICE_ID {
+        Err(api::Error::BadRequest("Transaction belongs to another service".to_owned()))?;
+    }
+    Ok(tx)
+}
+
+/// Returns public keys of the wallets which can be changed by the given transaction.
+fn affected_wallets<T: AsRef<dyn Snapshot>>(
+    currency_schema: &Schema<T>,
+    author: &PublicKey,
+    transaction: &WalletTransactions,
+) -> BTreeSet<PublicKey> {
+    let mut wallets = BTreeSet::new();
+    wallets.insert(*author);
+    let add_transfer = |wallets: &mut BTreeSet<PublicKey>, hash: &Hash| {
+        if let Some(transfer) = currency_schema.transfer(hash) {
+            wallets.insert(transfer.from);
+            wallets.insert(transfer.to);
+        }
+    };
+
+    match transaction {
+        WalletTransactions::Transfer(tx) => {
+            wallets.insert(tx.to);
+        }
+        WalletTransactions::TransferWithApproval(tx) => {
+            wallets.insert(tx.to);
+        }
+        WalletTransactions::Approve(tx) => add_transfer(&mut wallets, &tx.transfer_tx_hash),
+        WalletTransactions::PayInvoice(tx) => {
+            if let Some(invoice) = currency_schema.invoice(&tx.invoice_id) {
+                wallets.insert(invoice.receiver);
+            }
+        }
+        WalletTransactions::Refund(tx) => {
+            if let Some(settled) = currency_schema.settled_transfer(&tx.transfer_tx_hash) {
+                wallets.insert(settled.from);
+            }
+        }
+        WalletTransactions::Attest(tx) => {
+            let key = oracle::attestation_key(author, &tx.condition);
+            for hash in currency_schema.conditional_transfers(&key).iter() {
+                add_transfer(&mut wallets, &hash);
+            }
+        }
+        _ => {}
+    }
+    wallets
+}
+
+/// Returns hex representation of the given bytes.
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Parses bytes from their hex representation.
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 || !hex.is_ascii() {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+/// Returns proof of the last committed block.
+fn latest_block_proof<T: AsRef<dyn Snapshot>>(general_schema: &blockchain::Schema<T>) -> BlockProof {
+    let max_height = general_schema.block_hashes_by_height().len() - 1;
+    general_schema
+        .block_and_precommits(Height(max_height))
+        .unwrap()
+}
+
+/// Returns proof of the wallet with the given public key.
+fn wallet_proof<T: AsRef<dyn Snapshot>>(
+    general_schema: &blockchain::Schema<T>,
+    currency_schema: &Schema<T>,
+    pub_key: &PublicKey,
+) -> WalletProof {
+    WalletProof {
+        to_table: general_schema
+            .get_proof_to_service_table(CRYPTOCURRENCY_SERVICE_ID, WALLETS_TABLE),
+        to_wallet: currency_schema.wallets().get_proof(*pub_key),
+    }
+}
+
+/// Returns proof of the entries of the wallet with the given public key around the given
+/// height compacted into a checkpoint, and the balance of the wallet at that height.
+fn archived_balance_proof<T: AsRef<dyn Snapshot>>(
+    currency_schema: &Schema<T>,
+    pub_key: &PublicKey,
+    height: Height,
+) -> (ArchivedBalanceProof, Option<u64>) {
+    let checkpoints = currency_schema.wallet_checkpoints(pub_key);
+    let checkpoint = currency_schema.wallet_checkpoints_len_at(pub_key, height);
+    let archive = currency_schema.archived_history(pub_key, checkpoint);
+    let position = currency_schema.archived_history_len_at(pub_key, checkpoint, height);
+
+    let balance = match position.checked_sub(1) {
+        Some(index) => Some(archive.get(index).unwrap().balance),
+        None => checkpoint
+            .checked_sub(1)
+            .map(|previous| checkpoints.get(previous).unwrap().balance),
+    };
+    let proof = ArchivedBalanceProof {
+        checkpoints_len: checkpoints.len(),
+        checkpoints_hash: checkpoints.merkle_root(),
+        checkpoint,
+        checkpoints: checkpoints.get_range_proof(checkpoint.saturating_sub(1), checkpoint + 1),
+        entries: archive
+            .get_range_proof(position.saturating_sub(1), (position + 1).min(archive.len())),
+    };
+    (proof, balance)
+}
+
+/// Returns the page of the history of the wallet with the given public key
+/// from the `from` entry up to the `to` one.
+fn wallet_history_page<T: AsRef<dyn Snapshot>>(
+    explorer: &BlockchainExplorer,
+    currency_schema: &Schema<T>,
+    pub_key: &PublicKey,
+    from: u64,
+    to: u64,
+) -> WalletHistory {
+    let history = currency_schema.wallet_history(pub_key);
+    let proof = history.get_range_proof(from, to);
+
+    let transactions = history
+        .iter_from(from)
+        .take((to - from) as usize)
+        .filter(|entry| !entry.is_checkpoint())
+        .map(|entry| explorer.transaction_without_proof(&entry.tx_hash).unwrap())
+        .collect::<Vec<_>>();
+
+    WalletHistory {
+        proof,
+        transactions,
+    }
+}
+
+/// Returns proof of the name registry entry for the given name.
+fn name_proof<T: AsRef<dyn Snapshot>>(
+    general_schema: &blockchain::Schema<T>,
+    currency_schema: &Schema<T>,
+    name: &str,
+) -> NameProof {
+    NameProof {
+        to_table: general_schema.get_proof_to_service_table(CRYPTOCURRENCY_SERVICE_ID, NAMES_TABLE),
+        to_name: currency_schema.names().get_proof(wallet::name_key(name)),
+    }
+}
@@ -0,0 +1,74 @@
+use exonum::crypto::PublicKey;
+use exonum_derive::ProtobufConvert;
+use crate::proto;
+
+/// Minimal stake required to register an approver.
+pub const MIN_APPROVER_STAKE: u64 = 10;
+
+/// Approver can be named in transfers.
+pub const APPROVER_STATUS_ACTIVE: u32 = 0;
+/// Approver's stake was slashed below `MIN_APPROVER_STAKE`, it can't be named in new transfers.
+pub const APPROVER_STATUS_SLASHED: u32 = 1;
+
+/// Approver information stored in the database.
+#[derive(Clone, Debug, ProtobufConvert)]
+#[exonum(pb = "proto::Approver", serde_pb_convert)]
+pub struct Approver {
+    /// `PublicKey` of the approver.
+    pub pub_key: PublicKey,
+    /// Staked amount of currency.
+    pub stake: u64,
+    /// Approver status.
+    pub status: u32,
+    /// Number of the approved transfers.
+    pub approvals_count: u64,
+    /// Total amount of the approved transfers.
+    pub approved_amount: u64,
+    /// Total slashed amount of the stake.
+    pub slashed_amount: u64,
+}
+
+impl Approver {
+    /// Create new active Approver.
+    pub fn new(pub_key: PublicKey, stake: u64) -> Self {
+        Self {
+            pub_key,
+            stake,
+            status: APPROVER_STATUS_ACTIVE,
+            approvals_count: 0,
+            approved_amount: 0,
+            slashed_amount: 0,
+        }
+    }
+
+    /// Returns `true` if the approver can be named in transfers.
+    pub fn is_active(&self) -> bool {
+        self.status == APPROVER_STATUS_ACTIVE
+    }
+
+    /// Returns a copy of this approver with the approval of `amount` counted.
+    pub fn add_approval(self, amount: u64) -> Self {
+        Self {
+            approvals_count: self.approvals_count + 1,
+            approved_amount: self.approved_amount + amount,
+            ..self
+        }
+    }
+
+    /// Returns a copy of this approver with `amount` of the stake slashed. The approver
+    /// stays active while the rest of the stake is not less than `MIN_APPROVER_STAKE`.
+    pub fn slash(self, amount: u64) -> Self {
+        let stake = self.stake - amount;
+        let status = if stake < MIN_APPROVER_STAKE {
+            APPROVER_STATUS_SLASHED
+        } else {
+            self.status
+        };
+        Self {
+            stake,
+            status,
+            slashed_amount: self.slashed_amount + amount,
+            ..self
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use exonum::crypto::Hash;
+use exonum_derive::ProtobufConvert;
+use crate::proto;
+
+/// Wallet history gained an entry.
+pub const EVENT_HISTORY_ENTRY: u32 = 0;
+/// Transfer awaits the approval of the subscriber.
+pub const EVENT_TRANSFER_AWAITING_APPROVAL: u32 = 1;
+/// Pending transfer from or to the subscriber's wallet is settled.
+pub const EVENT_TRANSFER_SETTLED: u32 = 2;
+
+/// Maximum number of the events in the page of the event log.
+pub const MAX_EVENTS_PAGE_LEN: u64 = 100;
+/// Number of the latest events kept in the event log of a subscriber, the older events
+/// are pruned.
+pub const MAX_EVENTS_LOG_LEN: u64 = 1024;
+
+/// Event of the subscriber's event log stored in the database.
+#[derive(Clone, Debug, ProtobufConvert)]
+#[exonum(pb = "proto::WalletEvent", serde_pb_convert)]
+pub struct WalletEvent {
+    /// Kind of the event.
+    pub kind: u32,
+    /// `Hash` of the transaction which caused the event.
+    pub tx_hash: Hash,
+    /// `Hash` of the transfer the event is about, zero for the history events.
+    pub transfer_tx_hash: Hash,
+    /// Height of the block with the transaction.
+    pub height: u64,
+}
+
+impl WalletEvent {
+    /// Create new WalletEvent.
+    pub fn new(kind: u32, tx_hash: Hash, transfer_tx_hash: Hash, height: u64) -> Self {
+        Self {
+            kind,
+            tx_hash,
+            transfer_tx_hash,
+            height,
+        }
+    }
+}
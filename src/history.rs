@@ -0,0 +1,177 @@
+use exonum::crypto::{Hash, HashStream, PublicKey};
+use exonum_derive::ProtobufConvert;
+use crate::proto;
+
+/// Wallet creation.
+pub const KIND_CREATE_WALLET: u32 = 0;
+/// Issue of the currency.
+pub const KIND_ISSUE: u32 = 1;
+/// Transfer retaining the amount from the sender's balance.
+pub const KIND_TRANSFER: u32 = 2;
+/// Approval of the pending transfer.
+pub const KIND_APPROVE: u32 = 3;
+/// Invoice payment retaining the amount from the payer's balance.
+pub const KIND_PAY_INVOICE: u32 = 4;
+/// Refund of the settled transfer.
+pub const KIND_REFUND: u32 = 5;
+/// Oracle attestation settling or returning the conditional transfer.
+pub const KIND_ATTEST: u32 = 6;
+/// Transfer settled with the pre-signed approval.
+pub const KIND_TRANSFER_WITH_APPROVAL: u32 = 7;
+/// Approver registration staking the amount from the balance.
+pub const KIND_REGISTER_APPROVER: u32 = 8;
+/// Checkpoint replacing the compacted entries, its `tx_hash` is the commitment
+/// to the list of the wallet checkpoints.
+pub const KIND_CHECKPOINT: u32 = 9;
+/// Approver deregistration returning the rest of the stake to the balance.
+pub const KIND_UNREGISTER_APPROVER: u32 = 10;
+
+/// Length of the wallet history at which its entries are compacted into a checkpoint.
+pub const HISTORY_COMPACTION_THRESHOLD: u64 = 64;
+
+/// Maximum number of the entries in the page of the wallet history.
+pub const MAX_HISTORY_PAGE_LEN: u64 = 32;
+
+/// Returns the key of the archived entries compacted into the checkpoint with the given
+/// index of the wallet with the given public key.
+pub fn archive_key(pub_key: &PublicKey, checkpoint: u64) -> Hash {
+    HashStream::new()
+        .update(pub_key.as_ref())
+        .update(&checkpoint.to_le_bytes())
+        .hash()
+}
+
+/// Returns the commitment to the list of the wallet checkpoints with the given length
+/// and Merkle root.
+pub fn checkpoints_commitment(checkpoints_len: u64, checkpoints_hash: &Hash) -> Hash {
+    HashStream::new()
+        .update(&checkpoints_len.to_le_bytes())
+        .update(checkpoints_hash.as_ref())
+        .hash()
+}
+
+/// Transaction changing the state of a wallet, recorded into the wallet history.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Operation {
+    /// `Hash` of the transaction.
+    pub tx_hash: Hash,
+    /// Kind of the operation.
+    pub kind: u32,
+    /// `PublicKey` of the counterparty's wallet, zero if there is no counterparty.
+    pub counterparty: PublicKey,
+}
+
+impl Operation {
+    /// Create new Operation.
+    pub fn new(tx_hash: Hash, kind: u32, counterparty: PublicKey) -> Self {
+        Self {
+            tx_hash,
+            kind,
+            counterparty,
+        }
+    }
+
+    /// Create new Operation without a counterparty.
+    pub fn without_counterparty(tx_hash: Hash, kind: u32) -> Self {
+        Self::new(tx_hash, kind, PublicKey::zero())
+    }
+}
+
+/// Entry of the wallet history stored in the database.
+#[derive(Clone, Debug, ProtobufConvert)]
+#[exonum(pb = "proto::HistoryEntry", serde_pb_convert)]
+pub struct HistoryEntry {
+    /// `Hash` of the transaction.
+    pub tx_hash: Hash,
+    /// Kind of the operation.
+    pub kind: u32,
+    /// `PublicKey` of the counterparty's wallet, zero if there is no counterparty.
+    pub counterparty: PublicKey,
+    /// Change of the wallet balance.
+    pub balance_delta: i64,
+    /// Change of the wallet retained amount.
+    pub retained_delta: i64,
+    /// Balance of the wallet after the operation.
+    pub balance: u64,
+    /// Height of the block with the transaction.
+    pub height: u64,
+}
+
+impl HistoryEntry {
+    /// Create new HistoryEntry for the operation changing the wallet from the old balance
+    /// and retained amount to the new ones.
+    pub fn new(
+        operation: &Operation,
+        (old_balance, old_retained): (u64, u64),
+        (balance, retained_amount): (u64, u64),
+        height: u64,
+    ) -> Self {
+        Self {
+            tx_hash: operation.tx_hash,
+            kind: operation.kind,
+            counterparty: operation.counterparty,
+            balance_delta: balance as i64 - old_balance as i64,
+            retained_delta: retained_amount as i64 - old_retained as i64,
+            balance,
+            height,
+        }
+    }
+
+    /// Create new checkpoint HistoryEntry with the given commitment to the list of the
+    /// wallet checkpoints.
+    pub fn checkpoint(commitment: Hash, balance: u64, height: u64) -> Self {
+        Self {
+            tx_hash: commitment,
+            kind: KIND_CHECKPOINT,
+            counterparty: PublicKey::zero(),
+            balance_delta: 0,
+            retained_delta: 0,
+            balance,
+            height,
+        }
+    }
+
+    /// Returns `true` if the entry is the checkpoint replacing the compacted entries.
+    pub fn is_checkpoint(&self) -> bool {
+        self.kind == KIND_CHECKPOINT
+    }
+}
+
+/// Checkpoint summarizing the compacted entries of the wallet history stored in the database.
+#[derive(Clone, Debug, ProtobufConvert)]
+#[exonum(pb = "proto::HistoryCheckpoint", serde_pb_convert)]
+pub struct HistoryCheckpoint {
+    /// Merkle root of the list of the compacted entries.
+    pub entries_hash: Hash,
+    /// Number of the compacted entries.
+    pub entries_len: u64,
+    /// Height of the first compacted entry.
+    pub first_height: u64,
+    /// Height of the last compacted entry.
+    pub last_height: u64,
+    /// Balance of the wallet after the last compacted entry.
+    pub balance: u64,
+    /// Retained amount of the wallet after the last compacted entry.
+    pub retained_amount: u64,
+}
+
+impl HistoryCheckpoint {
+    /// Create new HistoryCheckpoint.
+    pub fn new(
+        entries_hash: Hash,
+        entries_len: u64,
+        first_height: u64,
+        last_height: u64,
+        balance: u64,
+        retained_amount: u64,
+    ) -> Self {
+        Self {
+            entries_hash,
+            entries_len,
+            first_height,
+            last_height,
+            balance,
+            retained_amount,
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use exonum::crypto::{Hash, PublicKey};
+use exonum_derive::ProtobufConvert;
+use crate::proto;
+
+/// Invoice is waiting for the payment.
+pub const INVOICE_STATUS_OPEN: u32 = 0;
+/// Invoice is paid.
+pub const INVOICE_STATUS_PAID: u32 = 1;
+/// Invoice was not paid until its due height.
+pub const INVOICE_STATUS_EXPIRED: u32 = 2;
+/// Payment of the invoice is waiting for the approval.
+pub const INVOICE_STATUS_PAYMENT_PENDING: u32 = 3;
+
+/// Invoice information stored in the database.
+#[derive(Clone, Debug, ProtobufConvert)]
+#[exonum(pb = "proto::Invoice", serde_pb_convert)]
+pub struct Invoice {
+    /// Identifier of the invoice, i.e. `Hash` of the `CreateInvoice` transaction.
+    pub id: Hash,
+    /// `PublicKey` of the receiver's wallet.
+    pub receiver: PublicKey,
+    /// Amount of currency to be paid.
+    pub amount: u64,
+    /// Height of the last block in which the invoice can be paid.
+    pub due_height: u64,
+    /// `PublicKey` of the approver of the payment.
+    pub approver: PublicKey,
+    /// `Hash` of the invoice description.
+    pub description_hash: Hash,
+    /// Invoice status.
+    pub status: u32,
+    /// `PublicKey` of the payer's wallet, zero while the invoice is open or expired.
+    pub payer: PublicKey,
+    /// `Hash` of the `PayInvoice` transaction, zero while the invoice is open or expired.
+    pub payment_tx_hash: Hash,
+}
+
+impl Invoice {
+    /// Create new open Invoice.
+    pub fn new(
+        id: Hash,
+        receiver: PublicKey,
+        amount: u64,
+        due_height: u64,
+        approver: PublicKey,
+        description_hash: Hash,
+    ) -> Self {
+        Self {
+            id,
+            receiver,
+            amount,
+            due_height,
+            approver,
+            description_hash,
+            status: INVOICE_STATUS_OPEN,
+            payer: PublicKey::zero(),
+            payment_tx_hash: Hash::zero(),
+        }
+    }
+
+    /// Returns `true` if the invoice is waiting for the payment.
+    pub fn is_open(&self) -> bool {
+        self.status == INVOICE_STATUS_OPEN
+    }
+
+    /// Returns a copy of this invoice with the payment by the given payer waiting
+    /// for the approval.
+    pub fn set_payment_pending(self, payer: PublicKey, payment_tx_hash: Hash) -> Self {
+        Self {
+            status: INVOICE_STATUS_PAYMENT_PENDING,
+            payer,
+            payment_tx_hash,
+            ..self
+        }
+    }
+
+    /// Returns a copy of this invoice with the pending payment settled.
+    pub fn set_paid(self) -> Self {
+        Self {
+            status: INVOICE_STATUS_PAID,
+            ..self
+        }
+    }
+
+    /// Returns a copy of this invoice with the pending payment rejected.
+    pub fn set_open(self) -> Self {
+        Self {
+            status: INVOICE_STATUS_OPEN,
+            payer: PublicKey::zero(),
+            payment_tx_hash: Hash::zero(),
+            ..self
+        }
+    }
+
+    /// Returns a copy of this invoice marked as expired.
+    pub fn set_expired(self) -> Self {
+        Self {
+            status: INVOICE_STATUS_EXPIRED,
+            ..self
+        }
+    }
+}
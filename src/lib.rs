@@ -7,15 +7,24 @@ use exonum::{
     crypto::Hash,
     helpers::fabric::{self, Context},
     messages::RawTransaction,
-    storage::Snapshot,
+    storage::{Fork, Snapshot},
 };
 use crate::transactions::WalletTransactions;
 use crate::schema::Schema;
 
 pub mod api;
+pub mod approver;
+pub mod event;
+pub mod history;
+pub mod invoice;
+pub mod oracle;
 pub mod proto;
 pub mod schema;
+pub mod sender_filter;
+pub mod settlement;
+pub mod statistics;
 pub mod transactions;
+pub mod verify;
 pub mod wallet;
 
 /// Unique service ID.
@@ -25,7 +34,6 @@ pub const SERVICE_NAME: &str = "cryptocurrency";
 /// Initial balance of the wallet.
 const INITIAL_BALANCE: u64 = 100;
 
-
 /// Exonum `Service` implementation.
 #[derive(Default, Debug)]
 pub struct Service;
@@ -48,8 +56,20 @@ impl blockchain::Service for Service {
         WalletTransactions::tx_from_raw(raw).map(Into::into)
     }
 
+    fn before_commit(&self, fork: &mut Fork) {
+        let mut schema = Schema::new(fork);
+        // Transactions upgrade the records before reading them, blocks without them are
+        // upgraded here.
+        schema.migrate();
+        let height = schema.next_height();
+        schema.expire_invoices(height);
+        schema.record_block_statistics(height);
+        schema.record_block_events(height);
+    }
+
     fn wire_api(&self, builder: &mut ServiceApiBuilder) {
         api::PublicApi::wire(builder);
+        api::PrivateApi::wire(builder);
     }
 }
 
@@ -0,0 +1,86 @@
+use exonum::{
+    crypto::{Hash, HashStream, PublicKey},
+    proto::ProtobufConvert,
+};
+use exonum_derive::ProtobufConvert;
+use failure::format_err;
+use crate::proto;
+
+/// Identifier of the condition of the transfer, zero for the approved transfers.
+///
+/// The zero condition is encoded as empty bytes, so the approved transfers keep the
+/// encoding of the transfers created before the conditions were introduced.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Condition(pub Hash);
+
+impl Condition {
+    /// Returns the condition of the approved transfers.
+    pub fn none() -> Self {
+        Condition(Hash::zero())
+    }
+
+    /// Returns `true` if this is the condition of the approved transfers.
+    pub fn is_none(&self) -> bool {
+        self.0 == Hash::zero()
+    }
+}
+
+impl ProtobufConvert for Condition {
+    type ProtoStruct = Vec<u8>;
+
+    fn to_pb(&self) -> Self::ProtoStruct {
+        if self.is_none() {
+            Vec::new()
+        } else {
+            self.0.as_ref().to_vec()
+        }
+    }
+
+    fn from_pb(pb: Self::ProtoStruct) -> Result<Self, failure::Error> {
+        if pb.is_empty() {
+            return Ok(Condition::none());
+        }
+        Hash::from_slice(&pb)
+            .map(Condition)
+            .ok_or_else(|| format_err!("Wrong length of the condition: {}", pb.len()))
+    }
+}
+
+/// Returns the key of the attestation of the given condition by the given oracle.
+pub fn attestation_key(oracle: &PublicKey, condition: &Hash) -> Hash {
+    HashStream::new()
+        .update(oracle.as_ref())
+        .update(condition.as_ref())
+        .hash()
+}
+
+/// Oracle attestation stored in the database.
+#[derive(Clone, Debug, ProtobufConvert)]
+#[exonum(pb = "proto::Attestation", serde_pb_convert)]
+pub struct Attestation {
+    /// `PublicKey` of the oracle.
+    pub oracle: PublicKey,
+    /// Identifier of the condition.
+    pub condition: Hash,
+    /// Outcome of the condition.
+    pub outcome: bool,
+    /// `Hash` of the `Attest` transaction.
+    pub attest_tx_hash: Hash,
+}
+
+impl Attestation {
+    /// Create new Attestation.
+    pub fn new(oracle: PublicKey, condition: Hash, outcome: bool, attest_tx_hash: Hash) -> Self {
+        Self {
+            oracle,
+            condition,
+            outcome,
+            attest_tx_hash,
+        }
+    }
+
+    /// Returns the key of this attestation.
+    pub fn key(&self) -> Hash {
+        attestation_key(&self.oracle, &self.condition)
+    }
+}
@@ -1,7 +1,13 @@
 #![allow(bare_trait_objects)]
 #![allow(renamed_and_removed_lints)]
 
-pub use self::cryptocurrency::{CreateWallet, Issue, Transfer, Approve, Wallet};
+pub use self::cryptocurrency::{
+    CreateWallet, Issue, Transfer, Approve, Wallet, SetSenderFilter, UpdateSenderList, SenderFilter,
+    CreateInvoice, PayInvoice, Invoice, Refund, SettledTransfer, Attest, Attestation,
+    TransferWithApproval, RegisterApprover, SlashApprover, Approver, Reindex,
+    HistoryEntry, HistoryCheckpoint, TransactionCounts, BlockStatistics,
+    TransferStatus, WalletEvent, UnregisterApprover,
+};
 
 include!(concat!(env!("OUT_DIR"), "/protobuf_mod.rs"));
 
@@ -1,8 +1,273 @@
+use std::collections::BTreeMap;
+
 use exonum::{
+    blockchain::{self, TransactionSet},
     crypto::{Hash, PublicKey},
-    storage::{Fork, ProofListIndex, ProofMapIndex, Snapshot},
+    helpers::Height,
+    storage::{Entry, Fork, ListIndex, MapIndex, ProofListIndex, ProofMapIndex, Snapshot},
+};
+use crate::{
+    approver::Approver,
+    event::{
+        WalletEvent, EVENT_HISTORY_ENTRY, EVENT_TRANSFER_AWAITING_APPROVAL, EVENT_TRANSFER_SETTLED,
+        MAX_EVENTS_LOG_LEN,
+    },
+    history::{
+        self, HistoryCheckpoint, HistoryEntry, Operation, HISTORY_COMPACTION_THRESHOLD,
+        KIND_APPROVE, KIND_CREATE_WALLET, KIND_ISSUE, KIND_REFUND, KIND_REGISTER_APPROVER,
+        KIND_TRANSFER, KIND_UNREGISTER_APPROVER,
+    },
+    invoice::Invoice,
+    oracle::{self, Attestation},
+    sender_filter::{SenderFilter, FILTER_MODE_NONE},
+    settlement::{SettledTransfer, TransferStatus, TRANSFER_STATUS_SETTLED},
+    statistics::{BlockStatistics, TransactionCounts},
+    wallet::{self, Wallet},
+    CRYPTOCURRENCY_SERVICE_ID,
+    INITIAL_BALANCE,
+    transactions::{Transfer, WalletTransactions},
 };
-use crate::{wallet::Wallet, INITIAL_BALANCE, transactions::Transfer};
+
+/// Version of the layout of the records written by this code.
+///
+/// Databases created before the version was stored have version 0, which is the layout
+/// of the baseline service.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Migration upgrading the stored records from the previous version of the layout.
+type Migration = fn(&mut Schema<&mut Fork>);
+
+/// Migrations of the stored records, the migration with index `i` upgrades version `i`
+/// to version `i + 1`.
+const MIGRATIONS: [Migration; SCHEMA_VERSION as usize] = [migrate_to_v1];
+
+/// Name of the index family with the wallet histories of the baseline layout, which are
+/// the lists of the hashes of the transactions.
+const LEGACY_WALLET_HISTORY: &str = "cryptocurrency.wallet_history";
+
+/// Convert the wallet histories of the baseline layout, build the indexes missing in it,
+/// see `Schema::reindex`, and record the statuses of the pending transfers.
+///
+/// Pending transfers of the baseline are encoded without the condition and are read
+/// as the approved transfers, so they are kept as is. Transfers approved in the baseline
+/// have no settlement records, so their statuses stay unknown.
+fn migrate_to_v1(schema: &mut Schema<&mut Fork>) {
+    convert_baseline_histories(schema);
+    schema.reindex();
+    record_pending_statuses(schema);
+}
+
+/// Replace the wallet histories of the baseline layout, which are the lists of the hashes
+/// of the transactions, with the history entries restored by replaying the transactions.
+///
+/// The baseline knows `CreateWallet`, `Issue`, `Transfer` and `Approve` transactions only.
+/// Panics if the history refers to a transaction missing in the blockchain or to another
+/// transaction.
+fn convert_baseline_histories(schema: &mut Schema<&mut Fork>) {
+    let wallets: Vec<Wallet> = schema.wallets().values().collect();
+    for wallet in wallets {
+        let key = wallet.pub_key;
+        let hashes: Vec<Hash> =
+            ProofListIndex::<_, Hash>::new_in_family(LEGACY_WALLET_HISTORY, &key, &schema.view)
+                .iter()
+                .collect();
+        if hashes.is_empty() {
+            continue;
+        }
+
+        let entries = {
+            let general_schema = blockchain::Schema::new(&schema.view);
+            let messages = general_schema.transactions();
+            let locations = general_schema.transactions_locations();
+            let transaction = |hash: &Hash| {
+                let message = messages
+                    .get(hash)
+                    .expect("Transaction of the wallet history is missing");
+                WalletTransactions::tx_from_raw(message.payload().clone())
+                    .expect("Transaction of the wallet history is not a wallet transaction")
+            };
+
+            let mut entries = Vec::with_capacity(hashes.len());
+            let mut state = (0, 0);
+            for hash in &hashes {
+                let (balance, retained) = state;
+                let (kind, counterparty, new_state) = match transaction(hash) {
+                    WalletTransactions::CreateWallet(_) => {
+                        (KIND_CREATE_WALLET, PublicKey::zero(), (INITIAL_BALANCE, 0))
+                    }
+                    WalletTransactions::Issue(tx) => {
+                        (KIND_ISSUE, PublicKey::zero(), (balance + tx.amount, retained))
+                    }
+                    WalletTransactions::Transfer(tx) => {
+                        (KIND_TRANSFER, tx.to, (balance - tx.amount, retained + tx.amount))
+                    }
+                    WalletTransactions::Approve(tx) => match transaction(&tx.transfer_tx_hash) {
+                        WalletTransactions::Transfer(transfer) if transfer.from == key => {
+                            (KIND_APPROVE, transfer.to, (balance, retained - transfer.amount))
+                        }
+                        WalletTransactions::Transfer(transfer) => {
+                            (KIND_APPROVE, transfer.from, (balance + transfer.amount, retained))
+                        }
+                        _ => panic!(
+                            "Approved transaction {:?} is not a transfer",
+                            tx.transfer_tx_hash
+                        ),
+                    },
+                    _ => panic!("Transaction {:?} is unknown to the baseline layout", hash),
+                };
+                let height = locations.get(hash).map_or(0, |location| location.block_height().0);
+                let operation = Operation::new(*hash, kind, counterparty);
+                entries.push(HistoryEntry::new(&operation, state, new_state, height));
+                state = new_state;
+            }
+            entries
+        };
+
+        {
+            let mut history = schema.wallet_history_mut(&key);
+            history.clear();
+            history.extend(entries);
+        }
+        if schema.wallet_history(&key).len() >= HISTORY_COMPACTION_THRESHOLD {
+            schema.compact_wallet_history(&key, wallet.balance, wallet.retained_amount);
+        }
+        let wallet = {
+            let history = schema.wallet_history(&key);
+            Wallet::new(
+                key,
+                &wallet.name,
+                wallet.balance,
+                wallet.retained_amount,
+                history.len(),
+                history.merkle_root(),
+            )
+        };
+        schema.wallets_mut().put(&key, wallet);
+
+        let mut legacy: ProofListIndex<&mut Fork, Hash> =
+            ProofListIndex::new_in_family(LEGACY_WALLET_HISTORY, &key, &mut schema.view);
+        legacy.clear();
+    }
+}
+
+/// Record the statuses of the pending transfers at the heights of the blocks with the
+/// transactions which made them.
+fn record_pending_statuses(schema: &mut Schema<&mut Fork>) {
+    let heights: Vec<(Hash, u64)> = {
+        let general_schema = blockchain::Schema::new(&schema.view);
+        let locations = general_schema.transactions_locations();
+        schema
+            .transfers()
+            .keys()
+            .map(|hash| {
+                let height = locations.get(&hash).map_or(0, |location| location.block_height().0);
+                (hash, height)
+            })
+            .collect()
+    };
+    for (hash, height) in heights {
+        if !schema.transfer_statuses().contains(&hash) {
+            schema.transfer_statuses_mut().put(&hash, TransferStatus::pending(hash, height));
+        }
+    }
+}
+
+/// Party of a pending transfer, used to index pending transfers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferParty {
+    /// Sender of the transfer.
+    Sender,
+    /// Receiver of the transfer.
+    Receiver,
+    /// Approver of the transfer, or the oracle of the conditional transfer.
+    Approver,
+}
+
+impl TransferParty {
+    /// All parties of a transfer.
+    pub const ALL: [TransferParty; 3] =
+        [TransferParty::Sender, TransferParty::Receiver, TransferParty::Approver];
+
+    /// Returns public key of this party of the given transfer.
+    pub fn key(self, transfer: &Transfer) -> PublicKey {
+        match self {
+            TransferParty::Sender => transfer.from,
+            TransferParty::Receiver => transfer.to,
+            TransferParty::Approver => transfer.approver,
+        }
+    }
+
+    fn index_name(self) -> &'static str {
+        match self {
+            TransferParty::Sender => "cryptocurrency.pending_transfers_by_sender",
+            TransferParty::Receiver => "cryptocurrency.pending_transfers_by_receiver",
+            TransferParty::Approver => "cryptocurrency.pending_transfers_by_approver",
+        }
+    }
+
+    /// Returns index of the roots of the pending transfers indexes of this party
+    /// in the state hash of the service.
+    pub fn roots_table(self) -> usize {
+        match self {
+            TransferParty::Sender => PENDING_SENDER_ROOTS_TABLE,
+            TransferParty::Receiver => PENDING_RECEIVER_ROOTS_TABLE,
+            TransferParty::Approver => PENDING_APPROVER_ROOTS_TABLE,
+        }
+    }
+
+    fn roots_name(self) -> &'static str {
+        match self {
+            TransferParty::Sender => "cryptocurrency.pending_transfers_by_sender_roots",
+            TransferParty::Receiver => "cryptocurrency.pending_transfers_by_receiver_roots",
+            TransferParty::Approver => "cryptocurrency.pending_transfers_by_approver_roots",
+        }
+    }
+}
+
+/// Index of the wallets table in the state hash of the service.
+pub const WALLETS_TABLE: usize = 0;
+/// Index of the pending transfers table in the state hash of the service.
+pub const TRANSFERS_TABLE: usize = 1;
+/// Index of the sender filters table in the state hash of the service.
+pub const SENDER_FILTERS_TABLE: usize = 2;
+/// Index of the name registry table in the state hash of the service.
+pub const NAMES_TABLE: usize = 3;
+/// Index of the invoices table in the state hash of the service.
+pub const INVOICES_TABLE: usize = 4;
+/// Index of the settled transfers table in the state hash of the service.
+pub const SETTLED_TRANSFERS_TABLE: usize = 5;
+/// Index of the attestations table in the state hash of the service.
+pub const ATTESTATIONS_TABLE: usize = 6;
+/// Index of the approvers table in the state hash of the service.
+pub const APPROVERS_TABLE: usize = 7;
+/// Index of the roots of the pending transfers indexes of the senders in the state hash.
+pub const PENDING_SENDER_ROOTS_TABLE: usize = 8;
+/// Index of the roots of the pending transfers indexes of the receivers in the state hash.
+pub const PENDING_RECEIVER_ROOTS_TABLE: usize = 9;
+/// Index of the roots of the pending transfers indexes of the approvers in the state hash.
+pub const PENDING_APPROVER_ROOTS_TABLE: usize = 10;
+/// Index of the block statistics table in the state hash of the service.
+pub const BLOCK_STATISTICS_TABLE: usize = 11;
+/// Index of the transfer statuses table in the state hash of the service.
+pub const TRANSFER_STATUSES_TABLE: usize = 12;
+/// Number of the tables in the state hash of the service.
+const TABLES_COUNT: usize = 13;
+
+/// Returns the number of the leading items of the list of the given length, ordered by
+/// their heights, with the heights not greater than the given one.
+fn len_at<F: Fn(u64) -> u64>(len: u64, height: Height, item_height: F) -> u64 {
+    let (mut low, mut high) = (0, len);
+    while low < high {
+        let middle = low + (high - low) / 2;
+        if item_height(middle) <= height.0 {
+            low = middle + 1;
+        } else {
+            high = middle;
+        }
+    }
+    low
+}
 
 /// Database schema for cryptocurrency.
 #[derive(Debug)]
@@ -30,9 +295,83 @@ where
         ProofMapIndex::new("cryptocurrency.wallets", &self.view)
     }
 
+    /// Returns `MapIndex` with wallets ordered by balance, from the richest one.
+    ///
+    /// Maps `wallet::balance_key` of the wallet balance and the public key to the public key.
+    pub fn wallets_by_balance(&self) -> MapIndex<&T, Vec<u8>, PublicKey> {
+        MapIndex::new("cryptocurrency.wallets_by_balance", &self.view)
+    }
+
     /// Returns history of the wallet with the given public key.
-    pub fn wallet_history(&self, public_key: &PublicKey) -> ProofListIndex<&T, Hash> {
-        ProofListIndex::new_in_family("cryptocurrency.wallet_history", public_key, &self.view)
+    pub fn wallet_history(&self, public_key: &PublicKey) -> ProofListIndex<&T, HistoryEntry> {
+        ProofListIndex::new_in_family("cryptocurrency.wallet_history_entries", public_key, &self.view)
+    }
+
+    /// Returns event log of the subscriber with the given public key.
+    ///
+    /// Events are keyed by their indexes in the log, only the events from
+    /// `first_wallet_event` are kept.
+    pub fn wallet_events(&self, public_key: &PublicKey) -> MapIndex<&T, u64, WalletEvent> {
+        MapIndex::new_in_family("cryptocurrency.wallet_events", public_key, &self.view)
+    }
+
+    /// Returns the number of the events ever added to the event log of the subscriber
+    /// with the given public key.
+    pub fn wallet_events_len(&self, public_key: &PublicKey) -> u64 {
+        let lens: MapIndex<&T, PublicKey, u64> =
+            MapIndex::new("cryptocurrency.wallet_events_len", &self.view);
+        lens.get(public_key).unwrap_or(0)
+    }
+
+    /// Returns the index of the oldest event kept in the event log of the subscriber
+    /// with the given public key.
+    pub fn first_wallet_event(&self, public_key: &PublicKey) -> u64 {
+        self.wallet_events_len(public_key).saturating_sub(MAX_EVENTS_LOG_LEN)
+    }
+
+    /// Returns checkpoints of the compacted history of the wallet with the given public key.
+    pub fn wallet_checkpoints(&self, public_key: &PublicKey) -> ProofListIndex<&T, HistoryCheckpoint> {
+        ProofListIndex::new_in_family("cryptocurrency.wallet_checkpoints", public_key, &self.view)
+    }
+
+    /// Returns history entries of the wallet with the given public key compacted into
+    /// the checkpoint with the given index.
+    pub fn archived_history(
+        &self,
+        public_key: &PublicKey,
+        checkpoint: u64,
+    ) -> ProofListIndex<&T, HistoryEntry> {
+        ProofListIndex::new_in_family(
+            "cryptocurrency.archived_history",
+            &history::archive_key(public_key, checkpoint),
+            &self.view,
+        )
+    }
+
+    /// Returns the number of entries in the history of the wallet with the given public key
+    /// which were recorded at heights not greater than the given one.
+    pub fn wallet_history_len_at(&self, public_key: &PublicKey, height: Height) -> u64 {
+        let history = self.wallet_history(public_key);
+        len_at(history.len(), height, |index| history.get(index).unwrap().height)
+    }
+
+    /// Returns the number of checkpoints of the wallet with the given public key which
+    /// compacted only the entries recorded at heights not greater than the given one.
+    pub fn wallet_checkpoints_len_at(&self, public_key: &PublicKey, height: Height) -> u64 {
+        let checkpoints = self.wallet_checkpoints(public_key);
+        len_at(checkpoints.len(), height, |index| checkpoints.get(index).unwrap().last_height)
+    }
+
+    /// Returns the number of entries compacted into the given checkpoint of the wallet with
+    /// the given public key which were recorded at heights not greater than the given one.
+    pub fn archived_history_len_at(
+        &self,
+        public_key: &PublicKey,
+        checkpoint: u64,
+        height: Height,
+    ) -> u64 {
+        let archive = self.archived_history(public_key, checkpoint);
+        len_at(archive.len(), height, |index| archive.get(index).unwrap().height)
     }
 
     /// Returns wallet for the given public key.
@@ -50,9 +389,186 @@ where
         self.transfers().get(hash)
     }
 
+    /// Returns `ProofMapIndex` with the statuses of the transfers.
+    pub fn transfer_statuses(&self) -> ProofMapIndex<&T, Hash, TransferStatus> {
+        ProofMapIndex::new("cryptocurrency.transfer_statuses", &self.view)
+    }
+
+    /// Returns status of the transfer with the given hash.
+    pub fn transfer_status(&self, hash: &Hash) -> Option<TransferStatus> {
+        self.transfer_statuses().get(hash)
+    }
+
+    /// Returns `ProofMapIndex` with sender filters of the wallets.
+    pub fn sender_filters(&self) -> ProofMapIndex<&T, PublicKey, SenderFilter> {
+        ProofMapIndex::new("cryptocurrency.sender_filters", &self.view)
+    }
+
+    /// Returns sender filter for the given public key.
+    pub fn sender_filter(&self, pub_key: &PublicKey) -> Option<SenderFilter> {
+        self.sender_filters().get(pub_key)
+    }
+
+    /// Returns sender list of the wallet with the given public key.
+    ///
+    /// Each listed sender is mapped to the hash of the transaction which listed it.
+    pub fn sender_list(&self, public_key: &PublicKey) -> ProofMapIndex<&T, PublicKey, Hash> {
+        ProofMapIndex::new_in_family("cryptocurrency.sender_list", public_key, &self.view)
+    }
+
+    /// Returns `true` if the receiver's sender filter accepts funds from the sender.
+    pub fn accepts_funds_from(&self, receiver: &PublicKey, sender: &PublicKey) -> bool {
+        match self.sender_filter(receiver) {
+            Some(filter) => filter.accepts(self.sender_list(receiver).contains(sender)),
+            None => true,
+        }
+    }
+
+    /// Returns `ProofMapIndex` with the name registry.
+    ///
+    /// Maps the hash of the normalized wallet name to the wallet public key.
+    pub fn names(&self) -> ProofMapIndex<&T, Hash, PublicKey> {
+        ProofMapIndex::new("cryptocurrency.names", &self.view)
+    }
+
+    /// Returns public key of the wallet registered under the given name.
+    pub fn resolve_name(&self, name: &str) -> Option<PublicKey> {
+        self.names().get(&wallet::name_key(name))
+    }
+
+    /// Returns `ProofMapIndex` with invoices.
+    pub fn invoices(&self) -> ProofMapIndex<&T, Hash, Invoice> {
+        ProofMapIndex::new("cryptocurrency.invoices", &self.view)
+    }
+
+    /// Returns invoice for the given identifier.
+    pub fn invoice(&self, id: &Hash) -> Option<Invoice> {
+        self.invoices().get(id)
+    }
+
+    /// Returns identifiers of the invoices which are due at the given height.
+    pub fn invoices_by_due_height(&self, height: Height) -> ListIndex<&T, Hash> {
+        ListIndex::new_in_family("cryptocurrency.invoices_by_due_height", &height.0, &self.view)
+    }
+
+    /// Returns identifiers of the invoices keyed by the hashes of their pending payments.
+    pub fn invoice_payments(&self) -> MapIndex<&T, Hash, Hash> {
+        MapIndex::new("cryptocurrency.invoice_payments", &self.view)
+    }
+
+    /// Returns `ProofMapIndex` with settled transfers.
+    pub fn settled_transfers(&self) -> ProofMapIndex<&T, Hash, SettledTransfer> {
+        ProofMapIndex::new("cryptocurrency.settled_transfers", &self.view)
+    }
+
+    /// Returns settled transfer for the given hash.
+    pub fn settled_transfer(&self, hash: &Hash) -> Option<SettledTransfer> {
+        self.settled_transfers().get(hash)
+    }
+
+    /// Returns refunds of the settled transfer with the given hash.
+    pub fn transfer_refunds(&self, transfer_tx: &Hash) -> ProofListIndex<&T, Hash> {
+        ProofListIndex::new_in_family("cryptocurrency.transfer_refunds", transfer_tx, &self.view)
+    }
+
+    /// Returns `ProofMapIndex` with oracle attestations.
+    ///
+    /// Attestations are keyed by `oracle::attestation_key` of the oracle and the condition.
+    pub fn attestations(&self) -> ProofMapIndex<&T, Hash, Attestation> {
+        ProofMapIndex::new("cryptocurrency.attestations", &self.view)
+    }
+
+    /// Returns attestation of the given condition by the given oracle.
+    pub fn attestation(&self, oracle: &PublicKey, condition: &Hash) -> Option<Attestation> {
+        self.attestations().get(&oracle::attestation_key(oracle, condition))
+    }
+
+    /// Returns hashes of the pending transfers waiting for the attestation with the given key.
+    pub fn conditional_transfers(&self, attestation_key: &Hash) -> ListIndex<&T, Hash> {
+        ListIndex::new_in_family("cryptocurrency.conditional_transfers", attestation_key, &self.view)
+    }
+
+    /// Returns `ProofMapIndex` with registered approvers.
+    pub fn approvers(&self) -> ProofMapIndex<&T, PublicKey, Approver> {
+        ProofMapIndex::new("cryptocurrency.approvers", &self.view)
+    }
+
+    /// Returns registered approver for the given public key.
+    pub fn approver(&self, pub_key: &PublicKey) -> Option<Approver> {
+        self.approvers().get(pub_key)
+    }
+
+    /// Returns `true` if the given key is a service key of one of the actual validators.
+    pub fn is_validator(&self, pub_key: &PublicKey) -> bool {
+        blockchain::Schema::new(&self.view)
+            .actual_configuration()
+            .validator_keys
+            .iter()
+            .any(|keys| &keys.service_key == pub_key)
+    }
+
+    /// Returns pending transfers of the given party with the given public key.
+    pub fn pending_transfers(
+        &self,
+        party: TransferParty,
+        public_key: &PublicKey,
+    ) -> ProofMapIndex<&T, Hash, Transfer> {
+        ProofMapIndex::new_in_family(party.index_name(), public_key, &self.view)
+    }
+
+    /// Returns `ProofMapIndex` with the roots of the pending transfers indexes of the given party.
+    ///
+    /// Parties without pending transfers are absent from this index.
+    pub fn pending_transfers_roots(&self, party: TransferParty) -> ProofMapIndex<&T, PublicKey, Hash> {
+        ProofMapIndex::new(party.roots_name(), &self.view)
+    }
+
+    /// Returns the number and the total amount of the pending transfers.
+    pub fn pending_totals(&self) -> (u64, u64) {
+        let count = Entry::new("cryptocurrency.pending_transfers_count", &self.view).get();
+        let value = Entry::new("cryptocurrency.pending_transfers_value", &self.view).get();
+        (count.unwrap_or(0), value.unwrap_or(0))
+    }
+
+    /// Returns `ProofListIndex` with the statistics of the blocks.
+    ///
+    /// Statistics are recorded for each block since the first block committed by the service
+    /// recording them, so the positions in this index are shifted from the block heights by
+    /// the height of its first element.
+    pub fn block_statistics(&self) -> ProofListIndex<&T, BlockStatistics> {
+        ProofListIndex::new("cryptocurrency.block_statistics", &self.view)
+    }
+
+    /// Returns the version of the layout of the stored records.
+    pub fn schema_version(&self) -> u32 {
+        Entry::new("cryptocurrency.schema_version", &self.view).get().unwrap_or(0)
+    }
+
+    /// Returns the height of the block which is being built on top of the current state.
+    pub fn next_height(&self) -> Height {
+        Height(blockchain::Schema::new(&self.view).block_hashes_by_height().len())
+    }
+
     /// Returns the state hash of cryptocurrency service.
+    ///
+    /// The position of each table is given by the `*_TABLE` constants, which are used
+    /// to prove the tables with `blockchain::Schema::get_proof_to_service_table`.
     pub fn state_hash(&self) -> Vec<Hash> {
-        vec![self.wallets().merkle_root(), self.transfers().merkle_root()]
+        let mut hashes = vec![Hash::zero(); TABLES_COUNT];
+        hashes[WALLETS_TABLE] = self.wallets().merkle_root();
+        hashes[TRANSFERS_TABLE] = self.transfers().merkle_root();
+        hashes[SENDER_FILTERS_TABLE] = self.sender_filters().merkle_root();
+        hashes[NAMES_TABLE] = self.names().merkle_root();
+        hashes[INVOICES_TABLE] = self.invoices().merkle_root();
+        hashes[SETTLED_TRANSFERS_TABLE] = self.settled_transfers().merkle_root();
+        hashes[ATTESTATIONS_TABLE] = self.attestations().merkle_root();
+        hashes[APPROVERS_TABLE] = self.approvers().merkle_root();
+        for &party in &TransferParty::ALL {
+            hashes[party.roots_table()] = self.pending_transfers_roots(party).merkle_root();
+        }
+        hashes[BLOCK_STATISTICS_TABLE] = self.block_statistics().merkle_root();
+        hashes[TRANSFER_STATUSES_TABLE] = self.transfer_statuses().merkle_root();
+        hashes
     }
 }
 
@@ -63,9 +579,335 @@ impl<'a> Schema<&'a mut Fork> {
         ProofMapIndex::new("cryptocurrency.wallets", &mut self.view)
     }
 
+    /// Returns mutable `MapIndex` with wallets ordered by balance.
+    pub fn wallets_by_balance_mut(&mut self) -> MapIndex<&mut Fork, Vec<u8>, PublicKey> {
+        MapIndex::new("cryptocurrency.wallets_by_balance", &mut self.view)
+    }
+
     /// Returns history for the wallet by the given public key.
-    pub fn wallet_history_mut(&mut self, public_key: &PublicKey) -> ProofListIndex<&mut Fork, Hash> {
-        ProofListIndex::new_in_family("cryptocurrency.wallet_history", public_key, &mut self.view)
+    pub fn wallet_history_mut(
+        &mut self,
+        public_key: &PublicKey,
+    ) -> ProofListIndex<&mut Fork, HistoryEntry> {
+        ProofListIndex::new_in_family(
+            "cryptocurrency.wallet_history_entries",
+            public_key,
+            &mut self.view,
+        )
+    }
+
+    /// Returns mutable entry with the version of the layout of the stored records.
+    pub fn schema_version_mut(&mut self) -> Entry<&mut Fork, u32> {
+        Entry::new("cryptocurrency.schema_version", &mut self.view)
+    }
+
+    /// Set the number and the total amount of the pending transfers.
+    fn set_pending_totals(&mut self, count: u64, value: u64) {
+        Entry::new("cryptocurrency.pending_transfers_count", &mut self.view).set(count);
+        Entry::new("cryptocurrency.pending_transfers_value", &mut self.view).set(value);
+    }
+
+    /// Returns mutable `ProofListIndex` with the statistics of the blocks.
+    pub fn block_statistics_mut(&mut self) -> ProofListIndex<&mut Fork, BlockStatistics> {
+        ProofListIndex::new("cryptocurrency.block_statistics", &mut self.view)
+    }
+
+    /// Returns the hashes, the authors and the contents of the transactions of the service
+    /// successfully executed in the block at the given height.
+    fn committed_transactions(&self, height: Height) -> Vec<(Hash, PublicKey, WalletTransactions)> {
+        let general_schema = blockchain::Schema::new(&self.view);
+        let results = general_schema.transaction_results();
+        let messages = general_schema.transactions();
+        general_schema
+            .block_transactions(height)
+            .iter()
+            .filter(|hash| results.get(hash).map_or(false, |result| result.0.is_ok()))
+            .filter_map(|hash| messages.get(&hash).map(|message| (hash, message)))
+            .filter(|(_, message)| message.payload().service_id() == CRYPTOCURRENCY_SERVICE_ID)
+            .filter_map(|(hash, message)| {
+                WalletTransactions::tx_from_raw(message.payload().clone())
+                    .ok()
+                    .map(|transaction| (hash, message.author(), transaction))
+            })
+            .collect()
+    }
+
+    /// Record the statistics of the transactions of the service successfully executed
+    /// in the block at the given height.
+    ///
+    /// The transferred volume is counted when the transfers are settled, so the transfers
+    /// returned to the senders are not counted.
+    pub fn record_block_statistics(&mut self, height: Height) {
+        let transactions = self.committed_transactions(height);
+
+        let mut counts = TransactionCounts::default();
+        let (mut volume_transferred, mut amount_issued) = (0, 0);
+        for (tx_hash, author, transaction) in &transactions {
+            counts.add(transaction);
+            if let WalletTransactions::Issue(tx) = transaction {
+                amount_issued += tx.amount;
+            }
+            for transfer_tx in self.affected_transfers(tx_hash, author, transaction) {
+                if self.is_settled_by(&transfer_tx, tx_hash) {
+                    let settled = self.settled_transfer(&transfer_tx);
+                    volume_transferred += settled.map_or(0, |settled| settled.amount);
+                }
+            }
+        }
+
+        let (pending_transfers, pending_value) = self.pending_totals();
+        self.block_statistics_mut().push(BlockStatistics::new(
+            height.0,
+            counts,
+            volume_transferred,
+            amount_issued,
+            pending_transfers,
+            pending_value,
+        ));
+    }
+
+    /// Add the events caused by the transactions of the service successfully executed
+    /// in the block at the given height to the event logs of the wallet owners.
+    ///
+    /// Each transaction adds the history entries it appended to the histories of its
+    /// parties, the transfer it left awaiting the approval and the transfers it settled.
+    pub fn record_block_events(&mut self, height: Height) {
+        let mut block_entries: BTreeMap<PublicKey, Vec<HistoryEntry>> = BTreeMap::new();
+        for (tx_hash, author, transaction) in self.committed_transactions(height) {
+            let mut parties = vec![author];
+            let mut awaiting_approver = None;
+            match &transaction {
+                WalletTransactions::Transfer(tx) => {
+                    parties.push(tx.to);
+                    if !tx.is_conditional() {
+                        awaiting_approver = Some(tx.approver);
+                    }
+                }
+                WalletTransactions::TransferWithApproval(tx) => parties.push(tx.to),
+                WalletTransactions::PayInvoice(tx) => {
+                    if let Some(invoice) = self.invoice(&tx.invoice_id) {
+                        parties.push(invoice.receiver);
+                        awaiting_approver = Some(invoice.approver);
+                    }
+                }
+                _ => {}
+            }
+            let transfers = self.affected_transfers(&tx_hash, &author, &transaction);
+            let settled: Vec<(Hash, PublicKey, PublicKey)> = transfers
+                .iter()
+                .filter_map(|transfer_tx| {
+                    let (from, to) = self.transfer_parties(transfer_tx)?;
+                    parties.push(from);
+                    parties.push(to);
+                    if self.is_settled_by(transfer_tx, &tx_hash) {
+                        Some((*transfer_tx, from, to))
+                    } else {
+                        None
+                    }
+                })
+                .collect();
+
+            let mut notified = Vec::new();
+            for key in parties {
+                if notified.contains(&key) {
+                    continue;
+                }
+                notified.push(key);
+                let entries = block_entries
+                    .entry(key)
+                    .or_insert_with(|| self.block_history_entries(&key, height));
+                let events: Vec<WalletEvent> = entries
+                    .iter()
+                    .filter(|entry| entry.tx_hash == tx_hash)
+                    .map(|_| WalletEvent::new(EVENT_HISTORY_ENTRY, tx_hash, Hash::zero(), height.0))
+                    .collect();
+                for event in events {
+                    self.add_event(&key, event);
+                }
+            }
+            if let Some(approver) = awaiting_approver {
+                if self.wallet(&approver).is_some() {
+                    let kind = EVENT_TRANSFER_AWAITING_APPROVAL;
+                    self.add_event(&approver, WalletEvent::new(kind, tx_hash, tx_hash, height.0));
+                }
+            }
+            for (transfer_tx, from, to) in settled {
+                let event = WalletEvent::new(EVENT_TRANSFER_SETTLED, tx_hash, transfer_tx, height.0);
+                self.add_event(&from, event.clone());
+                self.add_event(&to, event);
+            }
+        }
+    }
+
+    /// Returns the hashes of the transfers the committed transaction of the service could
+    /// settle, return or refund.
+    fn affected_transfers(
+        &self,
+        tx_hash: &Hash,
+        author: &PublicKey,
+        transaction: &WalletTransactions,
+    ) -> Vec<Hash> {
+        match transaction {
+            WalletTransactions::TransferWithApproval(_) => vec![*tx_hash],
+            WalletTransactions::Approve(tx) => vec![tx.transfer_tx_hash],
+            WalletTransactions::Refund(tx) => vec![tx.transfer_tx_hash],
+            WalletTransactions::Attest(tx) => {
+                let key = oracle::attestation_key(author, &tx.condition);
+                self.conditional_transfers(&key).iter().collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Returns `true` if the transfer was settled by the transaction with the given hash.
+    fn is_settled_by(&self, transfer_tx: &Hash, tx_hash: &Hash) -> bool {
+        self.transfer_status(transfer_tx).map_or(false, |status| {
+            status.status == TRANSFER_STATUS_SETTLED && status.resolution_tx_hash == *tx_hash
+        })
+    }
+
+    /// Returns the sender and the receiver of the transfer made by the transaction
+    /// with the given hash.
+    fn transfer_parties(&self, transfer_tx: &Hash) -> Option<(PublicKey, PublicKey)> {
+        if let Some(settled) = self.settled_transfer(transfer_tx) {
+            return Some((settled.from, settled.to));
+        }
+        let message = blockchain::Schema::new(&self.view).transactions().get(transfer_tx)?;
+        match WalletTransactions::tx_from_raw(message.payload().clone()).ok()? {
+            WalletTransactions::Transfer(tx) => Some((tx.from, tx.to)),
+            WalletTransactions::PayInvoice(tx) => {
+                self.invoice(&tx.invoice_id).map(|invoice| (message.author(), invoice.receiver))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the entries appended to the history of the wallet in the block at the given
+    /// height, including the ones compacted in that block.
+    fn block_history_entries(&self, key: &PublicKey, height: Height) -> Vec<HistoryEntry> {
+        let mut entries = Vec::new();
+        let checkpoints = self.wallet_checkpoints(key);
+        for index in (0..checkpoints.len()).rev() {
+            let last_height = checkpoints.get(index).map(|checkpoint| checkpoint.last_height);
+            if last_height != Some(height.0) {
+                break;
+            }
+            let mut archived: Vec<HistoryEntry> = self
+                .archived_history(key, index)
+                .iter()
+                .filter(|entry| entry.height == height.0)
+                .collect();
+            archived.extend(entries);
+            entries = archived;
+        }
+        entries.extend(
+            self.wallet_history(key)
+                .iter()
+                .filter(|entry| !entry.is_checkpoint() && entry.height == height.0),
+        );
+        entries
+    }
+
+    /// Rebuild the indexes derived from the wallets and the pending transfers: the name
+    /// registry entries, the indexes of the parties of the pending transfers, the totals
+    /// of the pending transfers and the index of the wallets by balance.
+    ///
+    /// A name already taken by another wallet is left to its owner. The statuses
+    /// of the transfers are not changed.
+    pub fn reindex(&mut self) {
+        let wallets: Vec<Wallet> = self.wallets().values().collect();
+        for wallet in &wallets {
+            let key = wallet::name_key(&wallet.name);
+            if !self.names().contains(&key) {
+                self.names_mut().put(&key, wallet.pub_key);
+            }
+        }
+        {
+            let mut by_balance = self.wallets_by_balance_mut();
+            by_balance.clear();
+            for wallet in &wallets {
+                let key = wallet::balance_key(wallet.balance, &wallet.pub_key);
+                by_balance.put(&key, wallet.pub_key);
+            }
+        }
+
+        let transfers: Vec<(Hash, Transfer)> = self.transfers().iter().collect();
+        let (mut count, mut value) = (0, 0);
+        for (hash, transfer) in transfers {
+            count += 1;
+            value += transfer.amount;
+            self.index_pending_transfer(&hash, transfer);
+        }
+        self.set_pending_totals(count, value);
+    }
+
+    /// Upgrade the stored records written by the previous versions of the layout
+    /// to `SCHEMA_VERSION`.
+    ///
+    /// Transactions upgrade the records before reading them. The upgrade is reverted
+    /// together with the failed transaction and repeated by the next one.
+    ///
+    /// Panics if the records were written by a newer version.
+    pub fn migrate(&mut self) {
+        let version = self.schema_version();
+        assert!(
+            version <= SCHEMA_VERSION,
+            "Database schema version {} is newer than supported {}",
+            version,
+            SCHEMA_VERSION
+        );
+        for migration in &MIGRATIONS[version as usize..] {
+            migration(self);
+        }
+        if version < SCHEMA_VERSION {
+            self.schema_version_mut().set(SCHEMA_VERSION);
+        }
+    }
+
+    /// Returns mutable event log of the subscriber with the given public key.
+    pub fn wallet_events_mut(
+        &mut self,
+        public_key: &PublicKey,
+    ) -> MapIndex<&mut Fork, u64, WalletEvent> {
+        MapIndex::new_in_family("cryptocurrency.wallet_events", public_key, &mut self.view)
+    }
+
+    /// Append the event to the event log of the subscriber and prune the events
+    /// older than the last `MAX_EVENTS_LOG_LEN` ones.
+    fn add_event(&mut self, subscriber: &PublicKey, event: WalletEvent) {
+        let index = self.wallet_events_len(subscriber);
+        {
+            let mut events = self.wallet_events_mut(subscriber);
+            events.put(&index, event);
+            if index >= MAX_EVENTS_LOG_LEN {
+                events.remove(&(index - MAX_EVENTS_LOG_LEN));
+            }
+        }
+        let mut lens: MapIndex<&mut Fork, PublicKey, u64> =
+            MapIndex::new("cryptocurrency.wallet_events_len", &mut self.view);
+        lens.put(subscriber, index + 1);
+    }
+
+    /// Returns mutable checkpoints of the compacted history of the wallet with the given public key.
+    pub fn wallet_checkpoints_mut(
+        &mut self,
+        public_key: &PublicKey,
+    ) -> ProofListIndex<&mut Fork, HistoryCheckpoint> {
+        ProofListIndex::new_in_family("cryptocurrency.wallet_checkpoints", public_key, &mut self.view)
+    }
+
+    /// Returns mutable history entries of the wallet with the given public key compacted into
+    /// the checkpoint with the given index.
+    pub fn archived_history_mut(
+        &mut self,
+        public_key: &PublicKey,
+        checkpoint: u64,
+    ) -> ProofListIndex<&mut Fork, HistoryEntry> {
+        ProofListIndex::new_in_family(
+            "cryptocurrency.archived_history",
+            &history::archive_key(public_key, checkpoint),
+            &mut self.view,
+        )
     }
 
     /// Returns mutable `ProofMapIndex` with not approved transfers.
@@ -73,18 +915,402 @@ impl<'a> Schema<&'a mut Fork> {
         ProofMapIndex::new("cryptocurrency.transfers", &mut self.view)
     }
 
-    /// Increase balance of the wallet and append new record to its history.
+    /// Returns mutable pending transfers of the given party with the given public key.
+    pub fn pending_transfers_mut(
+        &mut self,
+        party: TransferParty,
+        public_key: &PublicKey,
+    ) -> ProofMapIndex<&mut Fork, Hash, Transfer> {
+        ProofMapIndex::new_in_family(party.index_name(), public_key, &mut self.view)
+    }
+
+    /// Returns mutable `ProofMapIndex` with the roots of the pending transfers indexes of the given party.
+    pub fn pending_transfers_roots_mut(
+        &mut self,
+        party: TransferParty,
+    ) -> ProofMapIndex<&mut Fork, PublicKey, Hash> {
+        ProofMapIndex::new(party.roots_name(), &mut self.view)
+    }
+
+    /// Add the pending transfer to the indexes of its parties.
+    fn index_pending_transfer(&mut self, transfer_tx: &Hash, transfer: Transfer) {
+        for &party in &TransferParty::ALL {
+            let key = party.key(&transfer);
+            let root = {
+                let mut index = self.pending_transfers_mut(party, &key);
+                index.put(transfer_tx, transfer);
+                index.merkle_root()
+            };
+            self.pending_transfers_roots_mut(party).put(&key, root);
+        }
+    }
+
+    /// Add the pending transfer to `transfers` and to the indexes of its parties.
+    fn add_pending_transfer(&mut self, transfer_tx: &Hash, transfer: Transfer) {
+        self.index_pending_transfer(transfer_tx, transfer);
+        let (count, value) = self.pending_totals();
+        self.set_pending_totals(count + 1, value + transfer.amount);
+        let height = self.next_height().0;
+        self.transfer_statuses_mut().put(transfer_tx, TransferStatus::pending(*transfer_tx, height));
+        self.transfers_mut().put(transfer_tx, transfer);
+    }
+
+    /// Remove the pending transfer from `transfers` and from the indexes of its parties.
+    fn remove_pending_transfer(&mut self, transfer_tx: &Hash) {
+        let transfer = match self.transfer(transfer_tx) {
+            Some(transfer) => transfer,
+            None => return,
+        };
+        for &party in &TransferParty::ALL {
+            let key = party.key(&transfer);
+            let (root, is_empty) = {
+                let mut index = self.pending_transfers_mut(party, &key);
+                index.remove(transfer_tx);
+                (index.merkle_root(), index.keys().next().is_none())
+            };
+            if is_empty {
+                self.pending_transfers_roots_mut(party).remove(&key);
+            } else {
+                self.pending_transfers_roots_mut(party).put(&key, root);
+            }
+        }
+        let (count, value) = self.pending_totals();
+        self.set_pending_totals(count - 1, value - transfer.amount);
+        self.transfers_mut().remove(transfer_tx);
+    }
+
+    /// Returns mutable `ProofMapIndex` with the statuses of the transfers.
+    pub fn transfer_statuses_mut(&mut self) -> ProofMapIndex<&mut Fork, Hash, TransferStatus> {
+        ProofMapIndex::new("cryptocurrency.transfer_statuses", &mut self.view)
+    }
+
+    /// Returns mutable `ProofMapIndex` with sender filters of the wallets.
+    pub fn sender_filters_mut(&mut self) -> ProofMapIndex<&mut Fork, PublicKey, SenderFilter> {
+        ProofMapIndex::new("cryptocurrency.sender_filters", &mut self.view)
+    }
+
+    /// Returns mutable sender list of the wallet with the given public key.
+    pub fn sender_list_mut(&mut self, public_key: &PublicKey) -> ProofMapIndex<&mut Fork, PublicKey, Hash> {
+        ProofMapIndex::new_in_family("cryptocurrency.sender_list", public_key, &mut self.view)
+    }
+
+    /// Returns mutable `ProofMapIndex` with the name registry.
+    pub fn names_mut(&mut self) -> ProofMapIndex<&mut Fork, Hash, PublicKey> {
+        ProofMapIndex::new("cryptocurrency.names", &mut self.view)
+    }
+
+    /// Returns mutable `ProofMapIndex` with invoices.
+    pub fn invoices_mut(&mut self) -> ProofMapIndex<&mut Fork, Hash, Invoice> {
+        ProofMapIndex::new("cryptocurrency.invoices", &mut self.view)
+    }
+
+    /// Returns mutable identifiers of the invoices which are due at the given height.
+    pub fn invoices_by_due_height_mut(&mut self, height: Height) -> ListIndex<&mut Fork, Hash> {
+        ListIndex::new_in_family(
+            "cryptocurrency.invoices_by_due_height",
+            &height.0,
+            &mut self.view,
+        )
+    }
+
+    /// Returns mutable identifiers of the invoices keyed by the hashes of their pending payments.
+    pub fn invoice_payments_mut(&mut self) -> MapIndex<&mut Fork, Hash, Hash> {
+        MapIndex::new("cryptocurrency.invoice_payments", &mut self.view)
+    }
+
+    /// Returns mutable `ProofMapIndex` with settled transfers.
+    pub fn settled_transfers_mut(&mut self) -> ProofMapIndex<&mut Fork, Hash, SettledTransfer> {
+        ProofMapIndex::new("cryptocurrency.settled_transfers", &mut self.view)
+    }
+
+    /// Returns mutable refunds of the settled transfer with the given hash.
+    pub fn transfer_refunds_mut(&mut self, transfer_tx: &Hash) -> ProofListIndex<&mut Fork, Hash> {
+        ProofListIndex::new_in_family("cryptocurrency.transfer_refunds", transfer_tx, &mut self.view)
+    }
+
+    /// Returns mutable `ProofMapIndex` with oracle attestations.
+    pub fn attestations_mut(&mut self) -> ProofMapIndex<&mut Fork, Hash, Attestation> {
+        ProofMapIndex::new("cryptocurrency.attestations", &mut self.view)
+    }
+
+    /// Returns mutable hashes of the pending transfers waiting for the attestation with the given key.
+    pub fn conditional_transfers_mut(&mut self, attestation_key: &Hash) -> ListIndex<&mut Fork, Hash> {
+        ListIndex::new_in_family(
+            "cryptocurrency.conditional_transfers",
+            attestation_key,
+            &mut self.view,
+        )
+    }
+
+    /// Store the attestation and return the hashes of the transfers which were waiting for it.
     ///
-    /// Panics if there is no wallet with given public key.
-    pub fn increase_wallet_balance(&mut self, wallet: Wallet, amount: u64, transaction: &Hash) {
+    /// The hashes are kept in `conditional_transfers`, so that the events of the block
+    /// name the transfers resolved by the attestation.
+    pub fn attest(&mut self, attestation: Attestation) -> Vec<Hash> {
+        let key = attestation.key();
+        let transfers = self.conditional_transfers(&key).iter().collect();
+        self.attestations_mut().put(&key, attestation);
+        transfers
+    }
+
+    /// Returns mutable `ProofMapIndex` with registered approvers.
+    pub fn approvers_mut(&mut self) -> ProofMapIndex<&mut Fork, PublicKey, Approver> {
+        ProofMapIndex::new("cryptocurrency.approvers", &mut self.view)
+    }
+
+    /// Register the owner of the wallet as an approver staking `stake` from its balance.
+    pub fn register_approver(&mut self, wallet: Wallet, stake: u64, transaction: &Hash) {
+        let key = wallet.pub_key;
+        let operation = Operation::without_counterparty(*transaction, KIND_REGISTER_APPROVER);
+        self.decrease_wallet_balance(wallet, stake, &operation);
+        self.approvers_mut().put(&key, Approver::new(key, stake));
+    }
+
+    /// Remove the approver from the registry and return the rest of its stake
+    /// to its wallet.
+    pub fn unregister_approver(&mut self, approver: Approver, wallet: Wallet, transaction: &Hash) {
+        let operation = Operation::without_counterparty(*transaction, KIND_UNREGISTER_APPROVER);
+        self.increase_wallet_balance(wallet, approver.stake, &operation);
+        self.approvers_mut().remove(&approver.pub_key);
+    }
+
+    /// Slash `amount` of the approver's stake.
+    pub fn slash_approver(&mut self, approver: Approver, amount: u64) {
+        let approver = approver.slash(amount);
+        self.approvers_mut().put(&{approver.pub_key}, approver);
+    }
+
+    /// Count the approval of `amount` in the statistics of the registered approver.
+    pub fn record_approval(&mut self, pub_key: &PublicKey, amount: u64) {
+        if let Some(approver) = self.approver(pub_key) {
+            self.approvers_mut().put(pub_key, approver.add_approval(amount));
+        }
+    }
+
+    /// Record the transfer as settled by the given transaction and mark the invoice paid
+    /// by the transfer, if any, as paid.
+    pub fn settle_transfer(&mut self, transfer_tx: &Hash, transfer: &Transfer, transaction: &Hash) {
+        let refunds_hash = self.transfer_refunds(transfer_tx).merkle_root();
+        let settled = SettledTransfer::new(
+            *transfer_tx,
+            transfer.from,
+            transfer.to,
+            transfer.amount,
+            *transaction,
+            refunds_hash,
+        );
+        self.settled_transfers_mut().put(transfer_tx, settled);
+        self.resolve_invoice_payment(transfer_tx, true);
+
+        let height = self.next_height().0;
+        let status = TransferStatus::resolved(*transfer_tx, TRANSFER_STATUS_SETTLED, *transaction, height);
+        self.transfer_statuses_mut().put(transfer_tx, status);
+    }
+
+    /// Refund `amount` of the settled transfer from its receiver to its sender.
+    ///
+    /// Panics if there is no wallet of the sender or the receiver.
+    pub fn refund_transfer(&mut self, settled: SettledTransfer, amount: u64, transaction: &Hash) {
+        let receiver = self.wallet(&settled.to).unwrap();
+        let operation = Operation::new(*transaction, KIND_REFUND, settled.from);
+        self.decrease_wallet_balance(receiver, amount, &operation);
+        let sender = self.wallet(&settled.from).unwrap();
+        let operation = Operation::new(*transaction, KIND_REFUND, settled.to);
+        self.increase_wallet_balance(sender, amount, &operation);
+
+        let settled = {
+            let mut refunds = self.transfer_refunds_mut(&settled.transfer_tx_hash);
+            refunds.push(*transaction);
+            settled.add_refund(amount, refunds.merkle_root())
+        };
+        self.settled_transfers_mut().put(&{settled.transfer_tx_hash}, settled);
+    }
+
+    /// Create new open invoice.
+    pub fn create_invoice(&mut self, invoice: Invoice) {
+        self.invoices_by_due_height_mut(Height(invoice.due_height)).push(invoice.id);
+        self.invoices_mut().put(&{invoice.id}, invoice);
+    }
+
+    /// Mark the invoice as waiting for the approval of the payment by the given transaction.
+    ///
+    /// The invoice is paid when the transfer created by the transaction is approved.
+    pub fn pay_invoice(&mut self, invoice: Invoice, payer: &PublicKey, transaction: &Hash) {
+        let invoice = invoice.set_payment_pending(*payer, *transaction);
+        self.invoice_payments_mut().put(transaction, invoice.id);
+        self.invoices_mut().put(&{invoice.id}, invoice);
+    }
+
+    /// Resolve the invoice paid by the given transfer, if any.
+    ///
+    /// A rejected payment reopens the invoice, or expires it if its due height is passed.
+    fn resolve_invoice_payment(&mut self, transfer_tx: &Hash, settled: bool) {
+        let id = match self.invoice_payments().get(transfer_tx) {
+            Some(id) => id,
+            None => return,
+        };
+        self.invoice_payments_mut().remove(transfer_tx);
+
+        let invoice = self.invoice(&id).expect("Invoice is missing for its payment");
+        let invoice = if settled {
+            invoice.set_paid()
+        } else if self.next_height().0 > invoice.due_height {
+            invoice.set_open().set_expired()
+        } else {
+            invoice.set_open()
+        };
+        self.invoices_mut().put(&id, invoice);
+    }
+
+    /// Mark the invoices which are still open at their due height as expired.
+    pub fn expire_invoices(&mut self, height: Height) {
+        let due: Vec<Hash> = self.invoices_by_due_height(height).iter().collect();
+        for id in due {
+            let invoice = self.invoice(&id).expect("Invoice is missing in the due index");
+            if invoice.is_open() {
+                self.invoices_mut().put(&id, invoice.set_expired());
+            }
+        }
+    }
+
+    /// Set the mode of the sender filter of the wallet.
+    pub fn set_sender_filter_mode(&mut self, key: &PublicKey, mode: u32) {
+        let filter = match self.sender_filter(key) {
+            Some(filter) => filter.set_mode(mode),
+            None => {
+                let list_hash = self.sender_list(key).merkle_root();
+                SenderFilter::new(*key, mode, 0, list_hash)
+            }
+        };
+        self.sender_filters_mut().put(key, filter);
+    }
+
+    /// Add the sender to the sender list of the wallet or remove it from there.
+    pub fn update_sender_list(
+        &mut self,
+        key: &PublicKey,
+        sender: &PublicKey,
+        listed: bool,
+        transaction: &Hash,
+    ) {
+        let filter = self.sender_filter(key)
+            .unwrap_or_else(|| {
+                SenderFilter::new(*key, FILTER_MODE_NONE, 0, self.sender_list(key).merkle_root())
+            });
+        let filter = {
+            let mut list = self.sender_list_mut(key);
+            let mut list_len = filter.list_len;
+            if listed && !list.contains(sender) {
+                list.put(sender, *transaction);
+                list_len += 1;
+            } else if !listed && list.contains(sender) {
+                list.remove(sender);
+                list_len -= 1;
+            }
+            filter.set_list(list_len, list.merkle_root())
+        };
+        self.sender_filters_mut().put(key, filter);
+    }
+
+    /// Update balance and retained amount of the wallet and append new entry to its history.
+    fn update_wallet(
+        &mut self,
+        wallet: Wallet,
+        balance: u64,
+        retained_amount: u64,
+        operation: &Operation,
+    ) {
+        let height = self.next_height().0;
+        let key = wallet.pub_key;
+        let history_len = {
+            let mut history = self.wallet_history_mut(&key);
+            history.push(HistoryEntry::new(
+                operation,
+                (wallet.balance, wallet.retained_amount),
+                (balance, retained_amount),
+                height,
+            ));
+            history.len()
+        };
+        if history_len >= HISTORY_COMPACTION_THRESHOLD {
+            self.compact_wallet_history(&key, balance, retained_amount);
+        }
+
+        {
+            let mut by_balance = self.wallets_by_balance_mut();
+            by_balance.remove(&wallet::balance_key(wallet.balance, &key));
+            by_balance.put(&wallet::balance_key(balance, &key), key);
+        }
+
         let wallet = {
-            let mut history = self.wallet_history_mut(&wallet.pub_key);
-            history.push(*transaction);
-            let history_hash = history.merkle_root();
-            let balance = wallet.balance;
-            wallet.set_balance(balance + amount, history_hash)
+            let history = self.wallet_history(&key);
+            Wallet::new(
+                key,
+                &wallet.name,
+                balance,
+                retained_amount,
+                history.len(),
+                history.merkle_root(),
+            )
         };
-        self.wallets_mut().put(&{wallet.pub_key}, wallet);
+        self.wallets_mut().put(&key, wallet);
+    }
+
+    /// Move the history entries of the wallet recorded since the last checkpoint into
+    /// the archive and replace them with the checkpoint entry.
+    ///
+    /// The checkpoint entry commits to the list of the wallet checkpoints, each of which
+    /// commits to the archived entries, so that they remain provable.
+    fn compact_wallet_history(&mut self, key: &PublicKey, balance: u64, retained_amount: u64) {
+        let entries: Vec<HistoryEntry> = self
+            .wallet_history(key)
+            .iter()
+            .filter(|entry| !entry.is_checkpoint())
+            .collect();
+        let first_height = entries.first().map_or(0, |entry| entry.height);
+        let last_height = entries.last().map_or(0, |entry| entry.height);
+
+        let index = self.wallet_checkpoints(key).len();
+        let entries_len = entries.len() as u64;
+        let entries_hash = {
+            let mut archive = self.archived_history_mut(key, index);
+            archive.extend(entries);
+            archive.merkle_root()
+        };
+        let commitment = {
+            let mut checkpoints = self.wallet_checkpoints_mut(key);
+            checkpoints.push(HistoryCheckpoint::new(
+                entries_hash,
+                entries_len,
+                first_height,
+                last_height,
+                balance,
+                retained_amount,
+            ));
+            history::checkpoints_commitment(checkpoints.len(), &checkpoints.merkle_root())
+        };
+
+        let mut history = self.wallet_history_mut(key);
+        history.clear();
+        history.push(HistoryEntry::checkpoint(commitment, balance, last_height));
+    }
+
+    /// Increase balance of the wallet and append new record to its history.
+    ///
+    /// Panics if there is no wallet with given public key.
+    pub fn increase_wallet_balance(&mut self, wallet: Wallet, amount: u64, operation: &Operation) {
+        let balance = wallet.balance + amount;
+        let retained_amount = wallet.retained_amount;
+        self.update_wallet(wallet, balance, retained_amount, operation);
+    }
+
+    /// Decrease balance of the wallet and append new record to its history.
+    ///
+    /// Panics if there is no wallet with given public key.
+    pub fn decrease_wallet_balance(&mut self, wallet: Wallet, amount: u64, operation: &Operation) {
+        let balance = wallet.balance - amount;
+        let retained_amount = wallet.retained_amount;
+        self.update_wallet(wallet, balance, retained_amount, operation);
     }
 
     /// Decrease retained_amount of the wallet and append new record to its history.
@@ -94,18 +1320,39 @@ impl<'a> Schema<&'a mut Fork> {
         &mut self,
         wallet: Wallet,
         amount: u64,
-        transaction: &Hash,
+        operation: &Operation,
         transfer_tx: &Hash
     ) {
-        let wallet = {
-            let mut history = self.wallet_history_mut(&wallet.pub_key);
-            history.push(*transaction);
-            let history_hash = history.merkle_root();
-            let retained_amount = wallet.retained_amount;
-            wallet.set_retained_amount(retained_amount - amount, history_hash)
-        };
-        self.wallets_mut().put(&{wallet.pub_key}, wallet);
-        self.transfers_mut().remove(transfer_tx);
+        let balance = wallet.balance;
+        let retained_amount = wallet.retained_amount - amount;
+        self.update_wallet(wallet, balance, retained_amount, operation);
+        self.remove_pending_transfer(transfer_tx);
+    }
+
+    /// Return the retained amount of the returned transfer to the balance of the wallet,
+    /// record the given status of the transfer, either `TRANSFER_STATUS_REJECTED`
+    /// or `TRANSFER_STATUS_FILTERED`, and append new record to the wallet history.
+    ///
+    /// The invoice paid by the transfer, if any, waits for another payment.
+    ///
+    /// Panics if there is no wallet with given public key.
+    pub fn release_retained_amount(
+        &mut self,
+        wallet: Wallet,
+        amount: u64,
+        operation: &Operation,
+        transfer_tx: &Hash,
+        status: u32,
+    ) {
+        let balance = wallet.balance + amount;
+        let retained_amount = wallet.retained_amount - amount;
+        self.update_wallet(wallet, balance, retained_amount, operation);
+        self.remove_pending_transfer(transfer_tx);
+        self.resolve_invoice_payment(transfer_tx, false);
+
+        let height = self.next_height().0;
+        let status = TransferStatus::resolved(*transfer_tx, status, operation.tx_hash, height);
+        self.transfer_statuses_mut().put(transfer_tx, status);
     }
 
     /// Decrease balance of the wallet, increase retained amount and append new record to its history.
@@ -115,29 +1362,33 @@ impl<'a> Schema<&'a mut Fork> {
         &mut self,
         wallet: Wallet,
         amount: u64,
-        transaction: &Hash,
+        operation: &Operation,
         transfer: Transfer,
     ) {
-        let wallet = {
-            let mut history = self.wallet_history_mut(&wallet.pub_key);
-            history.push(*transaction);
-            let history_hash = history.merkle_root();
-            let balance = wallet.balance;
-            let retained_amount = wallet.retained_amount;
-            wallet.set_balance_and_retained_amount(balance - amount, retained_amount + amount, history_hash)
-        };
-        self.transfers_mut().put(transaction, transfer);
-        self.wallets_mut().put(&{wallet.pub_key}, wallet);
+        let transaction = &operation.tx_hash;
+        if transfer.is_conditional() {
+            let key = oracle::attestation_key(&transfer.approver, &transfer.condition.0);
+            self.conditional_transfers_mut(&key).push(*transaction);
+        }
+        self.add_pending_transfer(transaction, transfer);
+
+        let balance = wallet.balance - amount;
+        let retained_amount = wallet.retained_amount + amount;
+        self.update_wallet(wallet, balance, retained_amount, operation);
     }
 
-    /// Create new wallet and append first record to its history.
+    /// Create new wallet, register its name and append first record to its history.
     pub fn create_wallet(&mut self, key: &PublicKey, name: &str, transaction: &Hash) {
+        self.names_mut().put(&wallet::name_key(name), *key);
+        let height = self.next_height().0;
         let wallet = {
             let mut history = self.wallet_history_mut(key);
-            history.push(*transaction);
+            let operation = Operation::without_counterparty(*transaction, KIND_CREATE_WALLET);
+            history.push(HistoryEntry::new(&operation, (0, 0), (INITIAL_BALANCE, 0), height));
             let history_hash = history.merkle_root();
             Wallet::new(*key, name, INITIAL_BALANCE, 0, history.len(), history_hash)
         };
+        self.wallets_by_balance_mut().put(&wallet::balance_key(INITIAL_BALANCE, key), *key);
         self.wallets_mut().put(key, wallet);
     }
 }
\ No newline at end of file
@@ -0,0 +1,61 @@
+use exonum::crypto::{Hash, PublicKey};
+use exonum_derive::ProtobufConvert;
+use crate::proto;
+
+/// Sender filter accepting funds from any sender.
+pub const FILTER_MODE_NONE: u32 = 0;
+/// Sender filter accepting funds only from the listed senders.
+pub const FILTER_MODE_ALLOWLIST: u32 = 1;
+/// Sender filter accepting funds from anyone except the listed senders.
+pub const FILTER_MODE_BLOCKLIST: u32 = 2;
+
+/// Sender filter information stored in the database.
+#[derive(Clone, Debug, ProtobufConvert)]
+#[exonum(pb = "proto::SenderFilter", serde_pb_convert)]
+pub struct SenderFilter {
+    /// `PublicKey` of the wallet.
+    pub pub_key: PublicKey,
+    /// Filter mode.
+    pub mode: u32,
+    /// Length of the sender list.
+    pub list_len: u64,
+    /// `Hash` of the sender list.
+    pub list_hash: Hash,
+}
+
+impl SenderFilter {
+    /// Create new SenderFilter.
+    pub fn new(pub_key: PublicKey, mode: u32, list_len: u64, list_hash: Hash) -> Self {
+        Self {
+            pub_key,
+            mode,
+            list_len,
+            list_hash,
+        }
+    }
+
+    /// Returns `true` if the given mode is known.
+    pub fn is_valid_mode(mode: u32) -> bool {
+        mode <= FILTER_MODE_BLOCKLIST
+    }
+
+    /// Returns `true` if the filter accepts funds from a sender with the given
+    /// membership in the sender list.
+    pub fn accepts(&self, listed: bool) -> bool {
+        match self.mode {
+            FILTER_MODE_ALLOWLIST => listed,
+            FILTER_MODE_BLOCKLIST => !listed,
+            _ => true,
+        }
+    }
+
+    /// Returns a copy of this filter with updated mode.
+    pub fn set_mode(self, mode: u32) -> Self {
+        Self::new(self.pub_key, mode, self.list_len, self.list_hash)
+    }
+
+    /// Returns a copy of this filter with updated sender list.
+    pub fn set_list(self, list_len: u64, list_hash: Hash) -> Self {
+        Self::new(self.pub_key, self.mode, list_len, list_hash)
+    }
+}
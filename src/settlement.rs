@@ -0,0 +1,115 @@
+use exonum::crypto::{Hash, PublicKey};
+use exonum_derive::ProtobufConvert;
+use crate::proto;
+
+/// Transfer is waiting for the approval or the attestation.
+pub const TRANSFER_STATUS_PENDING: u32 = 0;
+/// Transfer is settled to the receiver.
+pub const TRANSFER_STATUS_SETTLED: u32 = 1;
+/// Transfer is returned to the sender by the attestation of the opposite outcome.
+pub const TRANSFER_STATUS_REJECTED: u32 = 2;
+/// Transfer is returned to the sender because the receiver stopped accepting funds
+/// from the sender before the approval or the attestation.
+pub const TRANSFER_STATUS_FILTERED: u32 = 3;
+
+/// Status of the transfer stored in the database.
+#[derive(Clone, Debug, ProtobufConvert)]
+#[exonum(pb = "proto::TransferStatus", serde_pb_convert)]
+pub struct TransferStatus {
+    /// `Hash` of the transfer transaction.
+    pub transfer_tx_hash: Hash,
+    /// Status of the transfer.
+    pub status: u32,
+    /// `Hash` of the transaction which settled or rejected the transfer, zero while
+    /// the transfer is pending.
+    pub resolution_tx_hash: Hash,
+    /// Height of the block in which the transfer got this status.
+    pub height: u64,
+}
+
+impl TransferStatus {
+    /// Create new pending TransferStatus.
+    pub fn pending(transfer_tx_hash: Hash, height: u64) -> Self {
+        Self {
+            transfer_tx_hash,
+            status: TRANSFER_STATUS_PENDING,
+            resolution_tx_hash: Hash::zero(),
+            height,
+        }
+    }
+
+    /// Create new TransferStatus of the transfer resolved by the given transaction.
+    pub fn resolved(transfer_tx_hash: Hash, status: u32, resolution_tx_hash: Hash, height: u64) -> Self {
+        Self {
+            transfer_tx_hash,
+            status,
+            resolution_tx_hash,
+            height,
+        }
+    }
+
+    /// Returns `true` if the transfer is waiting for the approval or the attestation.
+    pub fn is_pending(&self) -> bool {
+        self.status == TRANSFER_STATUS_PENDING
+    }
+}
+
+/// Settled transfer information stored in the database.
+#[derive(Clone, Debug, ProtobufConvert)]
+#[exonum(pb = "proto::SettledTransfer", serde_pb_convert)]
+pub struct SettledTransfer {
+    /// `Hash` of the transfer transaction.
+    pub transfer_tx_hash: Hash,
+    /// `PublicKey` of sender's wallet.
+    pub from: PublicKey,
+    /// `PublicKey` of receiver's wallet.
+    pub to: PublicKey,
+    /// Transferred amount of currency.
+    pub amount: u64,
+    /// `Hash` of the transaction which settled the transfer.
+    pub settlement_tx_hash: Hash,
+    /// Amount of currency refunded to the sender.
+    pub refunded_amount: u64,
+    /// Number of the refunds.
+    pub refunds_len: u64,
+    /// `Hash` of the list of the refund transactions.
+    pub refunds_hash: Hash,
+}
+
+impl SettledTransfer {
+    /// Create new SettledTransfer.
+    pub fn new(
+        transfer_tx_hash: Hash,
+        from: PublicKey,
+        to: PublicKey,
+        amount: u64,
+        settlement_tx_hash: Hash,
+        refunds_hash: Hash,
+    ) -> Self {
+        Self {
+            transfer_tx_hash,
+            from,
+            to,
+            amount,
+            settlement_tx_hash,
+            refunded_amount: 0,
+            refunds_len: 0,
+            refunds_hash,
+        }
+    }
+
+    /// Returns the amount which can still be refunded.
+    pub fn refundable_amount(&self) -> u64 {
+        self.amount - self.refunded_amount
+    }
+
+    /// Returns a copy of this transfer with the refund added.
+    pub fn add_refund(self, amount: u64, refunds_hash: Hash) -> Self {
+        Self {
+            refunded_amount: self.refunded_amount + amount,
+            refunds_len: self.refunds_len + 1,
+            refunds_hash,
+            ..self
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use exonum_derive::ProtobufConvert;
+use crate::{proto, transactions::WalletTransactions};
+
+/// Maximum number of blocks in the range of the statistics query.
+pub const MAX_STATISTICS_RANGE: u64 = 1000;
+
+/// Numbers of the successfully executed transactions of each type.
+#[derive(Clone, Debug, Default, PartialEq, ProtobufConvert)]
+#[exonum(pb = "proto::TransactionCounts", serde_pb_convert)]
+pub struct TransactionCounts {
+    /// Number of `Transfer` transactions.
+    pub transfer: u64,
+    /// Number of `Approve` transactions.
+    pub approve: u64,
+    /// Number of `Issue` transactions.
+    pub issue: u64,
+    /// Number of `CreateWallet` transactions, i.e. the number of new wallets.
+    pub create_wallet: u64,
+    /// Number of `SetSenderFilter` transactions.
+    pub set_sender_filter: u64,
+    /// Number of `UpdateSenderList` transactions.
+    pub update_sender_list: u64,
+    /// Number of `CreateInvoice` transactions.
+    pub create_invoice: u64,
+    /// Number of `PayInvoice` transactions.
+    pub pay_invoice: u64,
+    /// Number of `Refund` transactions.
+    pub refund: u64,
+    /// Number of `Attest` transactions.
+    pub attest: u64,
+    /// Number of `TransferWithApproval` transactions.
+    pub transfer_with_approval: u64,
+    /// Number of `RegisterApprover` transactions.
+    pub register_approver: u64,
+    /// Number of `SlashApprover` transactions.
+    pub slash_approver: u64,
+    /// Number of `Reindex` transactions.
+    pub reindex: u64,
+    /// Number of `UnregisterApprover` transactions.
+    pub unregister_approver: u64,
+}
+
+impl TransactionCounts {
+    /// Count the given transaction.
+    pub fn add(&mut self, transaction: &WalletTransactions) {
+        let count = match transaction {
+            WalletTransactions::Transfer(_) => &mut self.transfer,
+            WalletTransactions::Approve(_) => &mut self.approve,
+            WalletTransactions::Issue(_) => &mut self.issue,
+            WalletTransactions::CreateWallet(_) => &mut self.create_wallet,
+            WalletTransactions::SetSenderFilter(_) => &mut self.set_sender_filter,
+            WalletTransactions::UpdateSenderList(_) => &mut self.update_sender_list,
+            WalletTransactions::CreateInvoice(_) => &mut self.create_invoice,
+            WalletTransactions::PayInvoice(_) => &mut self.pay_invoice,
+            WalletTransactions::Refund(_) => &mut self.refund,
+            WalletTransactions::Attest(_) => &mut self.attest,
+            WalletTransactions::TransferWithApproval(_) => &mut self.transfer_with_approval,
+            WalletTransactions::RegisterApprover(_) => &mut self.register_approver,
+            WalletTransactions::SlashApprover(_) => &mut self.slash_approver,
+            WalletTransactions::Reindex(_) => &mut self.reindex,
+            WalletTransactions::UnregisterApprover(_) => &mut self.unregister_approver,
+        };
+        *count += 1;
+    }
+}
+
+/// Aggregates of the service activity in the specific block stored in the database.
+#[derive(Clone, Debug, ProtobufConvert)]
+#[exonum(pb = "proto::BlockStatistics", serde_pb_convert)]
+pub struct BlockStatistics {
+    /// Height of the block.
+    pub height: u64,
+    /// Numbers of the successfully executed transactions of each type.
+    pub transactions: TransactionCounts,
+    /// Total amount of the transfers settled in the block, including the paid invoices.
+    pub volume_transferred: u64,
+    /// Total amount of the issued currency.
+    pub amount_issued: u64,
+    /// Number of the pending transfers after the block.
+    pub pending_transfers: u64,
+    /// Total amount of the pending transfers after the block.
+    pub pending_value: u64,
+}
+
+impl BlockStatistics {
+    /// Create new BlockStatistics.
+    pub fn new(
+        height: u64,
+        transactions: TransactionCounts,
+        volume_transferred: u64,
+        amount_issued: u64,
+        pending_transfers: u64,
+        pending_value: u64,
+    ) -> Self {
+        Self {
+            height,
+            transactions,
+            volume_transferred,
+            amount_issued,
+            pending_transfers,
+            pending_value,
+        }
+    }
+}
@@ -1,76 +1,336 @@
 use exonum::{
     blockchain::{ExecutionError, ExecutionResult, Transaction, TransactionContext},
-    crypto::{Hash, PublicKey, SecretKey},
+    crypto::{self, CryptoHash, Hash, PublicKey, SecretKey, Signature},
     messages::{Message, RawTransaction, Signed},
+    storage::Fork,
 };
 use exonum_derive::{ProtobufConvert, TransactionSet};
 use failure::Fail;
-use crate::{proto, schema::Schema, CRYPTOCURRENCY_SERVICE_ID};
+use crate::{
+    approver::MIN_APPROVER_STAKE,
+    history::{
+        Operation, KIND_APPROVE, KIND_ATTEST, KIND_ISSUE, KIND_PAY_INVOICE, KIND_TRANSFER,
+        KIND_TRANSFER_WITH_APPROVAL,
+    },
+    invoice::{Invoice, INVOICE_STATUS_EXPIRED},
+    oracle::{Attestation, Condition},
+    proto,
+    schema::{Schema, TransferParty},
+    sender_filter::SenderFilter,
+    settlement::{TRANSFER_STATUS_FILTERED, TRANSFER_STATUS_REJECTED},
+    wallet::{self, Wallet},
+    CRYPTOCURRENCY_SERVICE_ID,
+};
 
-const ERROR_SENDER_SAME_AS_RECEIVER: u8 = 0;
-const ERROR_WRONG_SENDER: u8 = 1;
+/// Defines the `Error` enum together with its stable codes and the error catalogue.
+///
+/// Each variant is followed by its code and the description of the catalogue entry;
+/// the fields of the variant are the names of the details of the entry.
+macro_rules! define_errors {
+    (
+        $(#[$enum_attr:meta])*
+        pub enum $error:ident {
+            $(
+                $(#[$attr:meta])*
+                $name:ident $({
+                    $(
+                        $(#[$field_attr:meta])*
+                        $field:ident: $ty:ty
+                    ),* $(,)*
+                })* = $code:expr => $description:expr,
+            )*
+        }
+    ) => {
+        $(#[$enum_attr])*
+        #[derive(Debug, Fail)]
+        pub enum $error {
+            $(
+                $(#[$attr])*
+                $name $({
+                    $(
+                        $(#[$field_attr])*
+                        $field: $ty
+                    ),*
+                })*,
+            )*
+        }
 
-/// Error codes emitted by wallet transactions during execution.
-#[derive(Debug, Fail)]
-#[repr(u8)]
-pub enum Error {
-    /// Wallet already exists.
-    ///
-    /// Can be emitted by `CreateWallet`.
-    #[fail(display = "Wallet already exists")]
-    WalletAlreadyExists = 0,
+        /// Error catalogue: code, name, description and names of the details of each error.
+        const ERROR_CATALOGUE: &[(u8, &str, &str, &[&str])] = &[
+            $(($code, stringify!($name), $description, &[$($(stringify!($field)),*)*]),)*
+        ];
 
-    /// Sender doesn't exist.
-    ///
-    /// Can be emitted by `Transfer` or `Approve`.
-    #[fail(display = "Sender doesn't exist")]
-    SenderNotFound = 1,
+        impl $error {
+            /// Returns the stable numeric code of the error.
+            pub fn code(&self) -> u8 {
+                match self {
+                    $($error::$name { .. } => $code,)*
+                }
+            }
+        }
+    };
+}
 
-    /// Receiver doesn't exist.
+define_errors! {
+    /// Error codes emitted by wallet transactions during execution.
     ///
-    /// Can be emitted by `Transfer`, `Approve` or `Issue`.
-    #[fail(display = "Receiver doesn't exist")]
-    ReceiverNotFound = 2,
+    /// Codes are stable: a code is never reused for another error, and new
+    /// errors always get a fresh code. See `error_catalogue` for the full list.
+    pub enum Error {
+        /// Wallet already exists.
+        ///
+        /// Can be emitted by `CreateWallet`.
+        #[fail(display = "Wallet already exists")]
+        WalletAlreadyExists = 0 => "Wallet already exists",
 
-    /// Insufficient currency amount.
-    ///
-    /// Can be emitted by `Transfer` or `Approve`.
-    #[fail(display = "Insufficient currency amount")]
-    InsufficientCurrencyAmount = 3,
+        /// Sender doesn't exist.
+        ///
+        /// Can be emitted by `Transfer`, `Approve`, `PayInvoice` or `Refund`.
+        #[fail(display = "Sender doesn't exist")]
+        SenderNotFound = 1 => "Sender doesn't exist",
 
-    /// Transfer doesn't exist.
-    ///
-    /// Can be emitted by `Approve`.
-    #[fail(display = "Transfer doesn't exist")]
-    TransferNotFound = 4,
+        /// Receiver doesn't exist.
+        ///
+        /// Can be emitted by `Transfer`, `Approve`, `Issue`, `CreateInvoice`, `PayInvoice` or `Refund`.
+        #[fail(display = "Receiver doesn't exist")]
+        ReceiverNotFound = 2 => "Receiver doesn't exist",
 
-    /// Approver same as sender.
-    ///
-    /// Can be emitted by `Transfer`.
-    #[fail(display = "Approver same as sender")]
-    ApproverSameAsSender = 5,
+        /// Insufficient currency amount.
+        ///
+        /// Can be emitted by `Transfer`, `Approve`, `PayInvoice`, `Refund` or `RegisterApprover`.
+        #[fail(
+            display = "Insufficient currency amount: needed {}, available {}",
+            needed, available
+        )]
+        InsufficientCurrencyAmount {
+            /// Amount required by the transaction.
+            needed: u64,
+            /// Amount available in the wallet.
+            available: u64,
+        } = 3 => "Insufficient currency amount",
 
-    /// Approver same as receiver.
-    ///
-    /// Can be emitted by `Transfer`.
-    #[fail(display = "Approver same as receiver")]
-    ApproverSameAsReceiver = 6,
+        /// Transfer doesn't exist.
+        ///
+        /// Can be emitted by `Approve` or `Refund`.
+        #[fail(display = "Transfer doesn't exist")]
+        TransferNotFound = 4 => "Transfer doesn't exist",
 
-    /// The approver can't approve this transfer.
-    ///
-    /// Can be emitted by `Approve`.
-    #[fail(display = "The approver can't approve this transfer")]
-    WrongApprover = 7,
+        /// Approver same as sender.
+        ///
+        /// Can be emitted by `Transfer` or `PayInvoice`.
+        #[fail(display = "Approver same as sender")]
+        ApproverSameAsSender = 5 => "Approver same as sender",
+
+        /// Approver same as receiver.
+        ///
+        /// Can be emitted by `Transfer`, `CreateInvoice` or `PayInvoice`.
+        #[fail(display = "Approver same as receiver")]
+        ApproverSameAsReceiver = 6 => "Approver same as receiver",
+
+        /// The approver can't approve this transfer.
+        ///
+        /// Can be emitted by `Approve`.
+        #[fail(display = "The approver can't approve this transfer")]
+        WrongApprover = 7 => "The approver can't approve this transfer",
+
+        /// Receiver doesn't accept funds from the sender.
+        ///
+        /// Can be emitted by `Transfer`.
+        #[fail(display = "Receiver doesn't accept funds from the sender")]
+        SenderNotAccepted = 8 => "Receiver doesn't accept funds from the sender",
+
+        /// Unknown sender filter mode.
+        ///
+        /// Can be emitted by `SetSenderFilter`.
+        #[fail(display = "Unknown sender filter mode: {}", mode)]
+        UnknownSenderFilterMode {
+            /// Requested filter mode.
+            mode: u32,
+        } = 9 => "Unknown sender filter mode",
+
+        /// Wallet doesn't exist.
+        ///
+        /// Can be emitted by `SetSenderFilter`, `UpdateSenderList`, `RegisterApprover`
+        /// or `UnregisterApprover`.
+        #[fail(display = "Wallet doesn't exist")]
+        WalletNotFound = 10 => "Wallet doesn't exist",
+
+        /// Sender same as receiver.
+        ///
+        /// Can be emitted by `Transfer` or `PayInvoice`.
+        #[fail(display = "Sender same as receiver")]
+        SenderSameAsReceiver = 11 => "Sender same as receiver",
+
+        /// Transaction author differs from the sender.
+        ///
+        /// Can be emitted by `Transfer`.
+        #[fail(display = "Transaction author differs from the sender")]
+        WrongSender = 12 => "Transaction author differs from the sender",
+
+        /// Wallet name is already taken.
+        ///
+        /// Can be emitted by `CreateWallet`.
+        #[fail(display = "Wallet name is already taken")]
+        NameAlreadyTaken = 13 => "Wallet name is already taken",
+
+        /// Wallet name is empty.
+        ///
+        /// Can be emitted by `CreateWallet`.
+        #[fail(display = "Wallet name is empty")]
+        EmptyName = 14 => "Wallet name is empty",
+
+        /// Invoice doesn't exist.
+        ///
+        /// Can be emitted by `PayInvoice`.
+        #[fail(display = "Invoice doesn't exist")]
+        InvoiceNotFound = 15 => "Invoice doesn't exist",
+
+        /// Invoice is already paid or its payment waits for the approval.
+        ///
+        /// Can be emitted by `PayInvoice`.
+        #[fail(display = "Invoice is already paid")]
+        InvoiceAlreadyPaid = 16 => "Invoice is already paid",
+
+        /// Invoice is expired.
+        ///
+        /// Can be emitted by `PayInvoice`.
+        #[fail(display = "Invoice is expired")]
+        InvoiceExpired = 17 => "Invoice is expired",
+
+        /// Due height of the invoice is already passed.
+        ///
+        /// Can be emitted by `CreateInvoice`.
+        #[fail(
+            display = "Due height is already passed: due height {}, current height {}",
+            due_height, current_height
+        )]
+        DueHeightPassed {
+            /// Requested due height.
+            due_height: u64,
+            /// Height of the block being built.
+            current_height: u64,
+        } = 18 => "Due height is already passed",
+
+        /// Only the receiver of the transfer can refund it.
+        ///
+        /// Can be emitted by `Refund`.
+        #[fail(display = "Only the receiver of the transfer can refund it")]
+        NotTransferReceiver = 19 => "Only the receiver of the transfer can refund it",
+
+        /// Refund exceeds the not yet refunded amount of the transfer.
+        ///
+        /// Can be emitted by `Refund`.
+        #[fail(
+            display = "Refund exceeds the not yet refunded amount: requested {}, remaining {}",
+            requested, remaining
+        )]
+        RefundExceedsRemainder {
+            /// Requested refund amount.
+            requested: u64,
+            /// Not yet refunded amount of the transfer.
+            remaining: u64,
+        } = 20 => "Refund exceeds the not yet refunded amount",
+
+        /// Conditional transfer is settled by the oracle attestation only.
+        ///
+        /// Can be emitted by `Approve`.
+        #[fail(display = "Conditional transfer is settled by the oracle attestation only")]
+        ConditionalTransfer = 21 => "Conditional transfer is settled by the oracle attestation only",
+
+        /// Condition is already attested by the oracle.
+        ///
+        /// Can be emitted by `Transfer` or `Attest`.
+        #[fail(display = "Condition is already attested by the oracle")]
+        AlreadyAttested = 22 => "Condition is already attested by the oracle",
+
+        /// Approver's signature doesn't match the transfer.
+        ///
+        /// Can be emitted by `TransferWithApproval`.
+        #[fail(display = "Approver's signature doesn't match the transfer")]
+        InvalidApproverSignature = 23 => "Approver's signature doesn't match the transfer",
+
+        /// Approver isn't registered or was slashed.
+        ///
+        /// Can be emitted by `Transfer`, `TransferWithApproval`, `CreateInvoice`,
+        /// `PayInvoice`, `SlashApprover` or `UnregisterApprover`.
+        #[fail(display = "Approver isn't registered or was slashed")]
+        ApproverNotRegistered = 24 => "Approver isn't registered or was slashed",
+
+        /// Approver is already registered.
+        ///
+        /// Can be emitted by `RegisterApprover`.
+        #[fail(display = "Approver is already registered")]
+        ApproverAlreadyRegistered = 25 => "Approver is already registered",
+
+        /// Stake is less than required.
+        ///
+        /// Can be emitted by `RegisterApprover`.
+        #[fail(display = "Stake is less than required: staked {}, minimum {}", stake, minimum)]
+        StakeTooLow {
+            /// Offered stake.
+            stake: u64,
+            /// Minimal stake.
+            minimum: u64,
+        } = 26 => "Stake is less than required",
+
+        /// Transaction author is not a validator.
+        ///
+        /// Can be emitted by `SlashApprover` or `Reindex`.
+        #[fail(display = "Transaction author is not a validator")]
+        NotValidator = 27 => "Transaction author is not a validator",
+
+        /// Slashed amount is zero.
+        ///
+        /// Can be emitted by `SlashApprover`.
+        #[fail(display = "Slashed amount is zero")]
+        ZeroSlashAmount = 28 => "Slashed amount is zero",
+
+        /// Approver has pending transfers.
+        ///
+        /// Can be emitted by `UnregisterApprover`.
+        #[fail(display = "Approver has pending transfers")]
+        ApproverHasPendingTransfers = 29 => "Approver has pending transfers",
+    }
 }
 
 impl From<Error> for ExecutionError {
     fn from(value: Error) -> ExecutionError {
         let description = format!("{}", value);
-        ExecutionError::with_description(value as u8, description)
+        ExecutionError::with_description(value.code(), description)
     }
 }
 
+/// Entry of the error catalogue.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ErrorInfo {
+    /// Stable numeric code of the error.
+    pub code: u8,
+    /// Name of the error.
+    pub name: String,
+    /// Human-readable description of the error.
+    pub description: String,
+    /// Names of the details included into the error description.
+    pub details: Vec<String>,
+}
+
+/// Returns the catalogue of the errors emitted by wallet transactions.
+pub fn error_catalogue() -> Vec<ErrorInfo> {
+    ERROR_CATALOGUE
+        .iter()
+        .map(|&(code, name, description, details)| ErrorInfo {
+            code,
+            name: name.to_owned(),
+            description: description.to_owned(),
+            details: details.iter().map(|&detail| detail.to_owned()).collect(),
+        })
+        .collect()
+}
+
 /// Transfer `amount` of the currency from one wallet to another with approval by a third party.
+///
+/// If the `condition` is not zero, the `approver` is an oracle: the transfer is settled
+/// when the oracle attests the `condition` with the expected `outcome`, and is returned
+/// to the sender when the oracle attests the opposite outcome.
 #[derive(Clone, Copy, Debug, ProtobufConvert)]
 #[exonum(pb = "proto::Transfer", serde_pb_convert)]
 pub struct Transfer {
@@ -78,7 +338,7 @@ pub struct Transfer {
     pub from: PublicKey,
     /// `PublicKey` of receiver's wallet.
     pub to: PublicKey,
-    /// `PublicKey` of the transaction approver.
+    /// `PublicKey` of the transaction approver or the oracle.
     pub approver: PublicKey,
     /// Amount of currency to transfer.
     pub amount: u64,
@@ -86,6 +346,10 @@ pub struct Transfer {
     ///
     /// [idempotence]: https://en.wikipedia.org/wiki/Idempotence
     pub seed: u64,
+    /// Identifier of the condition attested by the oracle, zero for the approved transfers.
+    pub condition: Condition,
+    /// Outcome of the condition releasing the funds to the receiver.
+    pub outcome: bool,
 }
 
 /// Approve the transfer transaction.
@@ -122,6 +386,258 @@ pub struct CreateWallet {
     pub name: String,
 }
 
+/// Set the mode of the sender filter of the author's wallet.
+#[derive(Serialize, Deserialize, Clone, Debug, ProtobufConvert)]
+#[exonum(pb = "proto::SetSenderFilter")]
+pub struct SetSenderFilter {
+    /// Filter mode.
+    pub mode: u32,
+    /// Auxiliary number to guarantee [non-idempotence][idempotence] of transactions.
+    ///
+    /// [idempotence]: https://en.wikipedia.org/wiki/Idempotence
+    pub seed: u64,
+}
+
+/// Add the `sender` to the sender list of the author's wallet or remove it from there.
+#[derive(Clone, Debug, ProtobufConvert)]
+#[exonum(pb = "proto::UpdateSenderList", serde_pb_convert)]
+pub struct UpdateSenderList {
+    /// `PublicKey` of the sender's wallet.
+    pub sender: PublicKey,
+    /// Whether the sender should be present in the list.
+    pub listed: bool,
+    /// Auxiliary number to guarantee [non-idempotence][idempotence] of transactions.
+    ///
+    /// [idempotence]: https://en.wikipedia.org/wiki/Idempotence
+    pub seed: u64,
+}
+
+/// Create an invoice for `amount` of the currency payable to the author's wallet.
+///
+/// The identifier of the invoice is the hash of this transaction.
+#[derive(Clone, Debug, ProtobufConvert)]
+#[exonum(pb = "proto::CreateInvoice", serde_pb_convert)]
+pub struct CreateInvoice {
+    /// Amount of currency to be paid.
+    pub amount: u64,
+    /// Height of the last block in which the invoice can be paid.
+    pub due_height: u64,
+    /// `PublicKey` of the approver of the payment.
+    pub approver: PublicKey,
+    /// `Hash` of the invoice description.
+    pub description_hash: Hash,
+    /// Auxiliary number to guarantee [non-idempotence][idempotence] of transactions.
+    ///
+    /// [idempotence]: https://en.wikipedia.org/wiki/Idempotence
+    pub seed: u64,
+}
+
+/// Pay the invoice with the given identifier.
+///
+/// Creates a transfer from the author's wallet to the invoice receiver, which is
+/// approved with `Approve` referencing the hash of this transaction. The invoice
+/// is paid when the transfer is approved and reopened if the transfer is rejected.
+#[derive(Clone, Debug, ProtobufConvert)]
+#[exonum(pb = "proto::PayInvoice", serde_pb_convert)]
+pub struct PayInvoice {
+    /// Identifier of the invoice.
+    pub invoice_id: Hash,
+    /// Auxiliary number to guarantee [non-idempotence][idempotence] of transactions.
+    ///
+    /// [idempotence]: https://en.wikipedia.org/wiki/Idempotence
+    pub seed: u64,
+}
+
+/// Refund `amount` of the settled transfer to its sender.
+///
+/// Can be issued only by the receiver of the transfer. The total refunded amount
+/// can't exceed the amount of the transfer.
+#[derive(Clone, Debug, ProtobufConvert)]
+#[exonum(pb = "proto::Refund", serde_pb_convert)]
+pub struct Refund {
+    /// `Hash` of the settled transfer.
+    pub transfer_tx_hash: Hash,
+    /// Amount of currency to refund.
+    pub amount: u64,
+    /// Auxiliary number to guarantee [non-idempotence][idempotence] of transactions.
+    ///
+    /// [idempotence]: https://en.wikipedia.org/wiki/Idempotence
+    pub seed: u64,
+}
+
+/// Attest the outcome of the condition by the oracle.
+///
+/// Settles or returns all pending transfers waiting for this attestation.
+#[derive(Clone, Debug, ProtobufConvert)]
+#[exonum(pb = "proto::Attest", serde_pb_convert)]
+pub struct Attest {
+    /// Identifier of the condition.
+    pub condition: Hash,
+    /// Outcome of the condition.
+    pub outcome: bool,
+    /// Auxiliary number to guarantee [non-idempotence][idempotence] of transactions.
+    ///
+    /// [idempotence]: https://en.wikipedia.org/wiki/Idempotence
+    pub seed: u64,
+}
+
+/// Transfer `amount` of the currency from one wallet to another, settled immediately.
+///
+/// Carries the approver's detached signature over the contents of the corresponding
+/// `Transfer` (see `Transfer::sign_approval`), so the transfer never becomes pending.
+#[derive(Clone, Debug, ProtobufConvert)]
+#[exonum(pb = "proto::TransferWithApproval", serde_pb_convert)]
+pub struct TransferWithApproval {
+    /// `PublicKey` of sender's wallet.
+    pub from: PublicKey,
+    /// `PublicKey` of receiver's wallet.
+    pub to: PublicKey,
+    /// `PublicKey` of the transaction approver.
+    pub approver: PublicKey,
+    /// Amount of currency to transfer.
+    pub amount: u64,
+    /// Auxiliary number to guarantee [non-idempotence][idempotence] of transactions.
+    ///
+    /// [idempotence]: https://en.wikipedia.org/wiki/Idempotence
+    pub seed: u64,
+    /// Approver's signature over the contents of the corresponding `Transfer`.
+    pub approver_signature: Vec<u8>,
+}
+
+/// Register the author as an approver staking `stake` from its wallet.
+#[derive(Serialize, Deserialize, Clone, Debug, ProtobufConvert)]
+#[exonum(pb = "proto::RegisterApprover")]
+pub struct RegisterApprover {
+    /// Amount of currency to stake.
+    pub stake: u64,
+    /// Auxiliary number to guarantee [non-idempotence][idempotence] of transactions.
+    ///
+    /// [idempotence]: https://en.wikipedia.org/wiki/Idempotence
+    pub seed: u64,
+}
+
+/// Slash `amount` of the approver's stake for misconduct.
+///
+/// Can be issued by validators only. Slashed approver can't be named in new transfers.
+#[derive(Clone, Debug, ProtobufConvert)]
+#[exonum(pb = "proto::SlashApprover", serde_pb_convert)]
+pub struct SlashApprover {
+    /// `PublicKey` of the approver.
+    pub approver: PublicKey,
+    /// Amount of the stake to slash.
+    pub amount: u64,
+    /// Auxiliary number to guarantee [non-idempotence][idempotence] of transactions.
+    ///
+    /// [idempotence]: https://en.wikipedia.org/wiki/Idempotence
+    pub seed: u64,
+}
+
+/// Rebuild the indexes derived from the wallets and the pending transfers.
+///
+/// Can be issued by validators only.
+#[derive(Serialize, Deserialize, Clone, Debug, ProtobufConvert)]
+#[exonum(pb = "proto::Reindex")]
+pub struct Reindex {
+    /// Auxiliary number to guarantee [non-idempotence][idempotence] of transactions.
+    ///
+    /// [idempotence]: https://en.wikipedia.org/wiki/Idempotence
+    pub seed: u64,
+}
+
+/// Remove the author from the approvers and return the rest of its stake to its wallet.
+///
+/// The approver can't leave while it has pending transfers to approve.
+#[derive(Serialize, Deserialize, Clone, Debug, ProtobufConvert)]
+#[exonum(pb = "proto::UnregisterApprover")]
+pub struct UnregisterApprover {
+    /// Auxiliary number to guarantee [non-idempotence][idempotence] of transactions.
+    ///
+    /// [idempotence]: https://en.wikipedia.org/wiki/Idempotence
+    pub seed: u64,
+}
+
+/// Context of the wallet transaction execution.
+///
+/// Unlike `TransactionContext`, it can be created outside of the block execution,
+/// so the transactions can be also executed against a fork of the API snapshot.
+#[derive(Debug)]
+pub struct ExecutionContext<'a> {
+    fork: &'a mut Fork,
+    author: PublicKey,
+    tx_hash: Hash,
+}
+
+impl<'a> ExecutionContext<'a> {
+    /// Create new ExecutionContext.
+    pub fn new(fork: &'a mut Fork, author: PublicKey, tx_hash: Hash) -> Self {
+        Self {
+            fork,
+            author,
+            tx_hash,
+        }
+    }
+
+    /// Returns the author of the transaction.
+    pub fn author(&self) -> PublicKey {
+        self.author
+    }
+
+    /// Returns `Hash` of the transaction.
+    pub fn tx_hash(&self) -> Hash {
+        self.tx_hash
+    }
+
+    /// Returns the fork in which the transaction is executed.
+    pub fn fork(&mut self) -> &mut Fork {
+        self.fork
+    }
+}
+
+/// Execution of the wallet transactions in the given `ExecutionContext`.
+pub trait Execute {
+    /// Execute the transaction, see `Transaction::execute`.
+    fn execute(&self, context: ExecutionContext) -> ExecutionResult;
+}
+
+/// Implements `Transaction` for the wallet transactions by executing them
+/// in the `ExecutionContext` built from the `TransactionContext`.
+///
+/// The stored records are upgraded to `SCHEMA_VERSION` before the transaction reads them.
+/// The upgrade is reverted together with the failed transaction and repeated by the next one.
+macro_rules! impl_transaction {
+    ($($name:ident),*) => {
+        $(
+            impl Transaction for $name {
+                fn execute(&self, mut context: TransactionContext) -> ExecutionResult {
+                    let author = context.author();
+                    let tx_hash = context.tx_hash();
+                    Schema::new(context.fork()).migrate();
+                    let context = ExecutionContext::new(context.fork(), author, tx_hash);
+                    Execute::execute(self, context)
+                }
+            }
+        )*
+    };
+}
+
+impl_transaction!(
+    Transfer,
+    Approve,
+    Issue,
+    CreateWallet,
+    SetSenderFilter,
+    UpdateSenderList,
+    CreateInvoice,
+    PayInvoice,
+    Refund,
+    Attest,
+    TransferWithApproval,
+    RegisterApprover,
+    SlashApprover,
+    Reindex,
+    UnregisterApprover
+);
+
 /// Transaction group.
 #[derive(Serialize, Deserialize, Clone, Debug, TransactionSet)]
 pub enum WalletTransactions {
@@ -133,6 +649,55 @@ pub enum WalletTransactions {
     Issue(Issue),
     /// CreateWallet tx.
     CreateWallet(CreateWallet),
+    /// SetSenderFilter tx.
+    SetSenderFilter(SetSenderFilter),
+    /// UpdateSenderList tx.
+    UpdateSenderList(UpdateSenderList),
+    /// CreateInvoice tx.
+    CreateInvoice(CreateInvoice),
+    /// PayInvoice tx.
+    PayInvoice(PayInvoice),
+    /// Refund tx.
+    Refund(Refund),
+    /// Attest tx.
+    Attest(Attest),
+    /// TransferWithApproval tx.
+    TransferWithApproval(TransferWithApproval),
+    /// RegisterApprover tx.
+    RegisterApprover(RegisterApprover),
+    /// SlashApprover tx.
+    SlashApprover(SlashApprover),
+    /// Reindex tx.
+    Reindex(Reindex),
+    /// UnregisterApprover tx.
+    UnregisterApprover(UnregisterApprover),
+}
+
+impl Execute for WalletTransactions {
+    fn execute(&self, context: ExecutionContext) -> ExecutionResult {
+        match self {
+            WalletTransactions::Transfer(tx) => Execute::execute(tx, context),
+            WalletTransactions::Approve(tx) => Execute::execute(tx, context),
+            WalletTransactions::Issue(tx) => Execute::execute(tx, context),
+            WalletTransactions::CreateWallet(tx) => Execute::execute(tx, context),
+            WalletTransactions::SetSenderFilter(tx) => Execute::execute(tx, context),
+            WalletTransactions::UpdateSenderList(tx) => Execute::execute(tx, context),
+            WalletTransactions::CreateInvoice(tx) => Execute::execute(tx, context),
+            WalletTransactions::PayInvoice(tx) => Execute::execute(tx, context),
+            WalletTransactions::Refund(tx) => Execute::execute(tx, context),
+            WalletTransactions::Attest(tx) => Execute::execute(tx, context),
+            WalletTransactions::TransferWithApproval(tx) => Execute::execute(tx, context),
+            WalletTransactions::RegisterApprover(tx) => Execute::execute(tx, context),
+            WalletTransactions::SlashApprover(tx) => Execute::execute(tx, context),
+            WalletTransactions::Reindex(tx) => Execute::execute(tx, context),
+            WalletTransactions::UnregisterApprover(tx) => Execute::execute(tx, context),
+        }
+    }
+}
+
+/// Returns `true` if the given key belongs to an active registered approver.
+fn is_active_approver(schema: &Schema<&mut Fork>, pub_key: &PublicKey) -> bool {
+    schema.approver(pub_key).map_or(false, |approver| approver.is_active())
 }
 
 impl CreateWallet {
@@ -160,31 +725,68 @@ impl Transfer {
         sk: &SecretKey,
     ) -> Signed<RawTransaction> {
         Message::sign_transaction(
-            Self { from: pk, to, approver, amount, seed },
+            Self {
+                from: pk,
+                to,
+                approver,
+                amount,
+                seed,
+                condition: Condition::none(),
+                outcome: false,
+            },
             CRYPTOCURRENCY_SERVICE_ID,
             pk,
             sk,
         )
     }
-}
 
-impl Transaction for Transfer {
-    fn execute(&self, mut context: TransactionContext) -> ExecutionResult {
-        let from = &context.author();
-        let hash = context.tx_hash();
+    #[doc(hidden)]
+    #[allow(clippy::too_many_arguments)]
+    pub fn sign_conditional(
+        &pk: &PublicKey,
+        &to: &PublicKey,
+        &oracle: &PublicKey,
+        condition: Hash,
+        outcome: bool,
+        amount: u64,
+        seed: u64,
+        sk: &SecretKey,
+    ) -> Signed<RawTransaction> {
+        Message::sign_transaction(
+            Self {
+                from: pk,
+                to,
+                approver: oracle,
+                amount,
+                seed,
+                condition: Condition(condition),
+                outcome,
+            },
+            CRYPTOCURRENCY_SERVICE_ID,
+            pk,
+            sk,
+        )
+    }
 
-        let mut schema = Schema::new(context.fork());
+    /// Returns the approver's detached signature over the contents of the transfer.
+    pub fn sign_approval(&self, approver_sk: &SecretKey) -> Signature {
+        crypto::sign(self.hash().as_ref(), approver_sk)
+    }
 
+    /// Returns `true` if the transfer is settled by the oracle attestation.
+    pub fn is_conditional(&self) -> bool {
+        !self.condition.is_none()
+    }
+
+    /// Check the transfer and return the wallets of the sender and the receiver.
+    fn check(&self, schema: &Schema<&mut Fork>) -> Result<(Wallet, Wallet), ExecutionError> {
+        let from = &self.from;
         let to = &self.to;
         let approver = &self.approver;
         let amount = self.amount;
 
-        if from != &self.from {
-            return Err(ExecutionError::new(ERROR_WRONG_SENDER));
-        }
-
         if from == to {
-            return Err(ExecutionError::new(ERROR_SENDER_SAME_AS_RECEIVER));
+            Err(Error::SenderSameAsReceiver)?;
         }
 
         if approver == from {
@@ -197,18 +799,55 @@ impl Transaction for Transfer {
 
         let sender = schema.wallet(from)
             .ok_or(Error::SenderNotFound)?;
-        let _receiver = schema.wallet(to)
+        let receiver = schema.wallet(to)
             .ok_or(Error::ReceiverNotFound)?;
 
+        if !schema.accepts_funds_from(to, from) {
+            Err(Error::SenderNotAccepted)?;
+        }
+
+        if self.is_conditional() {
+            if schema.attestation(approver, &self.condition.0).is_some() {
+                Err(Error::AlreadyAttested)?;
+            }
+        } else if !is_active_approver(schema, approver) {
+            Err(Error::ApproverNotRegistered)?;
+        }
+
         if sender.balance < amount {
-            Err(Error::InsufficientCurrencyAmount)?;
+            Err(Error::InsufficientCurrencyAmount {
+                needed: amount,
+                available: sender.balance,
+            })?;
         }
 
-        schema.retain_amount_from_wallet_balance(sender, amount, &hash, *self);
+        Ok((sender, receiver))
+    }
+
+    /// Check the transfer and retain its amount from the sender's balance.
+    fn retain(&self, schema: &mut Schema<&mut Fork>, hash: &Hash, kind: u32) -> ExecutionResult {
+        let (sender, _receiver) = self.check(schema)?;
+        let operation = Operation::new(*hash, kind, self.to);
+        schema.retain_amount_from_wallet_balance(sender, self.amount, &operation, *self);
         Ok(())
     }
 }
 
+impl Execute for Transfer {
+    fn execute(&self, mut context: ExecutionContext) -> ExecutionResult {
+        let from = &context.author();
+        let hash = context.tx_hash();
+
+        let mut schema = Schema::new(context.fork());
+
+        if from != &self.from {
+            Err(Error::WrongSender)?;
+        }
+
+        self.retain(&mut schema, &hash, KIND_TRANSFER)
+    }
+}
+
 impl Approve {
     #[doc(hidden)]
     pub fn sign(
@@ -226,8 +865,8 @@ impl Approve {
     }
 }
 
-impl Transaction for Approve {
-    fn execute(&self, mut context: TransactionContext) -> ExecutionResult {
+impl Execute for Approve {
+    fn execute(&self, mut context: ExecutionContext) -> ExecutionResult {
         let approver = &context.author();
         let hash = &context.tx_hash();
         let transfer_tx_hash = &self.transfer_tx_hash;
@@ -241,6 +880,10 @@ impl Transaction for Approve {
         let to = &transfer.to;
         let amount = transfer.amount;
 
+        if transfer.is_conditional() {
+            Err(Error::ConditionalTransfer)?;
+        }
+
         if approver != &transfer.approver {
             Err(Error::WrongApprover)?;
         }
@@ -251,18 +894,46 @@ impl Transaction for Approve {
             .ok_or(Error::ReceiverNotFound)?;
 
         if sender.retained_amount < amount {
-            Err(Error::InsufficientCurrencyAmount)?;
+            Err(Error::InsufficientCurrencyAmount {
+                needed: amount,
+                available: sender.retained_amount,
+            })?;
+        }
+
+        // The receiver stopped accepting funds from the sender after the transfer,
+        // so the transfer is filtered and the retained amount returns to the sender.
+        if !schema.accepts_funds_from(to, from) {
+            let operation = Operation::new(*hash, KIND_APPROVE, *to);
+            schema.release_retained_amount(
+                sender,
+                amount,
+                &operation,
+                transfer_tx_hash,
+                TRANSFER_STATUS_FILTERED,
+            );
+            return Ok(());
         }
 
-        schema.decrease_retained_amount(sender, amount, hash, transfer_tx_hash);
-        schema.increase_wallet_balance(receiver, amount, hash);
+        let operation = Operation::new(*hash, KIND_APPROVE, *to);
+        schema.decrease_retained_amount(sender, amount, &operation, transfer_tx_hash);
+        let operation = Operation::new(*hash, KIND_APPROVE, *from);
+        schema.increase_wallet_balance(receiver, amount, &operation);
+        schema.settle_transfer(transfer_tx_hash, &transfer, hash);
+        schema.record_approval(approver, amount);
 
         Ok(())
     }
 }
 
-impl Transaction for Issue {
-    fn execute(&self, mut context: TransactionContext) -> ExecutionResult {
+impl Issue {
+    #[doc(hidden)]
+    pub fn sign(pk: &PublicKey, amount: u64, seed: u64, sk: &SecretKey) -> Signed<RawTransaction> {
+        Message::sign_transaction(Self { amount, seed }, CRYPTOCURRENCY_SERVICE_ID, *pk, sk)
+    }
+}
+
+impl Execute for Issue {
+    fn execute(&self, mut context: ExecutionContext) -> ExecutionResult {
         let pub_key = &context.author();
         let hash = context.tx_hash();
 
@@ -270,7 +941,8 @@ impl Transaction for Issue {
 
         if let Some(wallet) = schema.wallet(pub_key) {
             let amount = self.amount;
-            schema.increase_wallet_balance(wallet, amount, &hash);
+            let operation = Operation::without_counterparty(hash, KIND_ISSUE);
+            schema.increase_wallet_balance(wallet, amount, &operation);
             Ok(())
         } else {
             Err(Error::ReceiverNotFound)?
@@ -278,19 +950,511 @@ impl Transaction for Issue {
     }
 }
 
-impl Transaction for CreateWallet {
-    fn execute(&self, mut context: TransactionContext) -> ExecutionResult {
+impl Execute for CreateWallet {
+    fn execute(&self, mut context: ExecutionContext) -> ExecutionResult {
         let pub_key = &context.author();
         let hash = context.tx_hash();
 
         let mut schema = Schema::new(context.fork());
 
-        if schema.wallet(pub_key).is_none() {
-            let name = &self.name;
-            schema.create_wallet(pub_key, name, &hash);
-            Ok(())
-        } else {
-            Err(Error::WalletAlreadyExists)?
+        if schema.wallet(pub_key).is_some() {
+            Err(Error::WalletAlreadyExists)?;
+        }
+
+        let name = &self.name;
+
+        if wallet::normalize_name(name).is_empty() {
+            Err(Error::EmptyName)?;
+        }
+
+        if schema.resolve_name(name).is_some() {
+            Err(Error::NameAlreadyTaken)?;
+        }
+
+        schema.create_wallet(pub_key, name, &hash);
+        Ok(())
+    }
+}
+
+impl SetSenderFilter {
+    #[doc(hidden)]
+    pub fn sign(pk: &PublicKey, mode: u32, seed: u64, sk: &SecretKey) -> Signed<RawTransaction> {
+        Message::sign_transaction(Self { mode, seed }, CRYPTOCURRENCY_SERVICE_ID, *pk, sk)
+    }
+}
+
+impl Execute for SetSenderFilter {
+    fn execute(&self, mut context: ExecutionContext) -> ExecutionResult {
+        let pub_key = &context.author();
+
+        let mut schema = Schema::new(context.fork());
+
+        if !SenderFilter::is_valid_mode(self.mode) {
+            Err(Error::UnknownSenderFilterMode { mode: self.mode })?;
+        }
+
+        schema.wallet(pub_key)
+            .ok_or(Error::WalletNotFound)?;
+
+        schema.set_sender_filter_mode(pub_key, self.mode);
+        Ok(())
+    }
+}
+
+impl UpdateSenderList {
+    #[doc(hidden)]
+    pub fn sign(
+        pk: &PublicKey,
+        &sender: &PublicKey,
+        listed: bool,
+        seed: u64,
+        sk: &SecretKey,
+    ) -> Signed<RawTransaction> {
+        Message::sign_transaction(
+            Self { sender, listed, seed },
+            CRYPTOCURRENCY_SERVICE_ID,
+            *pk,
+            sk,
+        )
+    }
+}
+
+impl Execute for UpdateSenderList {
+    fn execute(&self, mut context: ExecutionContext) -> ExecutionResult {
+        let pub_key = &context.author();
+        let hash = context.tx_hash();
+
+        let mut schema = Schema::new(context.fork());
+
+        schema.wallet(pub_key)
+            .ok_or(Error::WalletNotFound)?;
+
+        schema.update_sender_list(pub_key, &self.sender, self.listed, &hash);
+        Ok(())
+    }
+}
+
+impl CreateInvoice {
+    #[doc(hidden)]
+    pub fn sign(
+        pk: &PublicKey,
+        amount: u64,
+        due_height: u64,
+        &approver: &PublicKey,
+        description_hash: Hash,
+        seed: u64,
+        sk: &SecretKey,
+    ) -> Signed<RawTransaction> {
+        Message::sign_transaction(
+            Self { amount, due_height, approver, description_hash, seed },
+            CRYPTOCURRENCY_SERVICE_ID,
+            *pk,
+            sk,
+        )
+    }
+}
+
+impl Execute for CreateInvoice {
+    fn execute(&self, mut context: ExecutionContext) -> ExecutionResult {
+        let receiver = &context.author();
+        let hash = context.tx_hash();
+
+        let mut schema = Schema::new(context.fork());
+
+        if &self.approver == receiver {
+            Err(Error::ApproverSameAsReceiver)?;
+        }
+
+        schema.wallet(receiver)
+            .ok_or(Error::ReceiverNotFound)?;
+
+        if !is_active_approver(&schema, &self.approver) {
+            Err(Error::ApproverNotRegistered)?;
+        }
+
+        let current_height = schema.next_height().0;
+        if self.due_height < current_height {
+            Err(Error::DueHeightPassed {
+                due_height: self.due_height,
+                current_height,
+            })?;
+        }
+
+        let invoice = Invoice::new(
+            hash,
+            *receiver,
+            self.amount,
+            self.due_height,
+            self.approver,
+            self.description_hash,
+        );
+        schema.create_invoice(invoice);
+        Ok(())
+    }
+}
+
+impl PayInvoice {
+    #[doc(hidden)]
+    pub fn sign(pk: &PublicKey, invoice_id: Hash, seed: u64, sk: &SecretKey) -> Signed<RawTransaction> {
+        Message::sign_transaction(Self { invoice_id, seed }, CRYPTOCURRENCY_SERVICE_ID, *pk, sk)
+    }
+}
+
+impl Execute for PayInvoice {
+    fn execute(&self, mut context: ExecutionContext) -> ExecutionResult {
+        let payer = &context.author();
+        let hash = context.tx_hash();
+
+        let mut schema = Schema::new(context.fork());
+
+        let invoice = schema.invoice(&self.invoice_id)
+            .ok_or(Error::InvoiceNotFound)?;
+
+        if invoice.status == INVOICE_STATUS_EXPIRED {
+            Err(Error::InvoiceExpired)?;
+        }
+
+        if !invoice.is_open() {
+            Err(Error::InvoiceAlreadyPaid)?;
         }
+
+        let transfer = Transfer {
+            from: *payer,
+            to: invoice.receiver,
+            approver: invoice.approver,
+            amount: invoice.amount,
+            seed: self.seed,
+            condition: Condition::none(),
+            outcome: false,
+        };
+        transfer.retain(&mut schema, &hash, KIND_PAY_INVOICE)?;
+
+        schema.pay_invoice(invoice, payer, &hash);
+        Ok(())
     }
-}
\ No newline at end of file
+}
+
+impl Refund {
+    #[doc(hidden)]
+    pub fn sign(
+        pk: &PublicKey,
+        transfer_tx_hash: Hash,
+        amount: u64,
+        seed: u64,
+        sk: &SecretKey,
+    ) -> Signed<RawTransaction> {
+        Message::sign_transaction(
+            Self { transfer_tx_hash, amount, seed },
+            CRYPTOCURRENCY_SERVICE_ID,
+            *pk,
+            sk,
+        )
+    }
+}
+
+impl Execute for Refund {
+    fn execute(&self, mut context: ExecutionContext) -> ExecutionResult {
+        let author = &context.author();
+        let hash = context.tx_hash();
+        let amount = self.amount;
+
+        let mut schema = Schema::new(context.fork());
+
+        let settled = schema.settled_transfer(&self.transfer_tx_hash)
+            .ok_or(Error::TransferNotFound)?;
+
+        if author != &settled.to {
+            Err(Error::NotTransferReceiver)?;
+        }
+
+        let remaining = settled.refundable_amount();
+        if amount > remaining {
+            Err(Error::RefundExceedsRemainder {
+                requested: amount,
+                remaining,
+            })?;
+        }
+
+        let receiver = schema.wallet(&settled.to)
+            .ok_or(Error::ReceiverNotFound)?;
+        schema.wallet(&settled.from)
+            .ok_or(Error::SenderNotFound)?;
+
+        if receiver.balance < amount {
+            Err(Error::InsufficientCurrencyAmount {
+                needed: amount,
+                available: receiver.balance,
+            })?;
+        }
+
+        schema.refund_transfer(settled, amount, &hash);
+        Ok(())
+    }
+}
+
+impl Attest {
+    #[doc(hidden)]
+    pub fn sign(
+        pk: &PublicKey,
+        condition: Hash,
+        outcome: bool,
+        seed: u64,
+        sk: &SecretKey,
+    ) -> Signed<RawTransaction> {
+        Message::sign_transaction(
+            Self { condition, outcome, seed },
+            CRYPTOCURRENCY_SERVICE_ID,
+            *pk,
+            sk,
+        )
+    }
+}
+
+impl Execute for Attest {
+    fn execute(&self, mut context: ExecutionContext) -> ExecutionResult {
+        let oracle = &context.author();
+        let hash = context.tx_hash();
+
+        let mut schema = Schema::new(context.fork());
+
+        if schema.attestation(oracle, &self.condition).is_some() {
+            Err(Error::AlreadyAttested)?;
+        }
+
+        let attestation = Attestation::new(*oracle, self.condition, self.outcome, hash);
+        let transfer_hashes = schema.attest(attestation);
+
+        for transfer_tx_hash in &transfer_hashes {
+            let transfer = schema.transfer(transfer_tx_hash)
+                .expect("Conditional transfer is missing");
+            let sender = schema.wallet(&transfer.from).unwrap();
+            let receiver = schema.wallet(&transfer.to).unwrap();
+            let amount = transfer.amount;
+
+            if transfer.outcome == self.outcome
+                && schema.accepts_funds_from(&transfer.to, &transfer.from)
+            {
+                let operation = Operation::new(hash, KIND_ATTEST, transfer.to);
+                schema.decrease_retained_amount(sender, amount, &operation, transfer_tx_hash);
+                let operation = Operation::new(hash, KIND_ATTEST, transfer.from);
+                schema.increase_wallet_balance(receiver, amount, &operation);
+                schema.settle_transfer(transfer_tx_hash, &transfer, &hash);
+            } else {
+                let status = if transfer.outcome == self.outcome {
+                    TRANSFER_STATUS_FILTERED
+                } else {
+                    TRANSFER_STATUS_REJECTED
+                };
+                let operation = Operation::new(hash, KIND_ATTEST, transfer.to);
+                schema.release_retained_amount(sender, amount, &operation, transfer_tx_hash, status);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl TransferWithApproval {
+    #[doc(hidden)]
+    pub fn sign(
+        &pk: &PublicKey,
+        &to: &PublicKey,
+        &approver: &PublicKey,
+        amount: u64,
+        seed: u64,
+        approver_signature: &Signature,
+        sk: &SecretKey,
+    ) -> Signed<RawTransaction> {
+        Message::sign_transaction(
+            Self {
+                from: pk,
+                to,
+                approver,
+                amount,
+                seed,
+                approver_signature: approver_signature.as_ref().to_vec(),
+            },
+            CRYPTOCURRENCY_SERVICE_ID,
+            pk,
+            sk,
+        )
+    }
+
+    /// Returns the transfer approved by the approver's signature.
+    pub fn transfer(&self) -> Transfer {
+        Transfer {
+            from: self.from,
+            to: self.to,
+            approver: self.approver,
+            amount: self.amount,
+            seed: self.seed,
+            condition: Condition::none(),
+            outcome: false,
+        }
+    }
+}
+
+impl Execute for TransferWithApproval {
+    fn execute(&self, mut context: ExecutionContext) -> ExecutionResult {
+        let from = &context.author();
+        let hash = context.tx_hash();
+
+        let mut schema = Schema::new(context.fork());
+
+        if from != &self.from {
+            Err(Error::WrongSender)?;
+        }
+
+        let transfer = self.transfer();
+
+        let signature = Signature::from_slice(&self.approver_signature)
+            .ok_or(Error::InvalidApproverSignature)?;
+        if !crypto::verify(&signature, transfer.hash().as_ref(), &self.approver) {
+            Err(Error::InvalidApproverSignature)?;
+        }
+
+        let (sender, receiver) = transfer.check(&schema)?;
+        let amount = transfer.amount;
+
+        let operation = Operation::new(hash, KIND_TRANSFER_WITH_APPROVAL, transfer.to);
+        schema.decrease_wallet_balance(sender, amount, &operation);
+        let operation = Operation::new(hash, KIND_TRANSFER_WITH_APPROVAL, transfer.from);
+        schema.increase_wallet_balance(receiver, amount, &operation);
+        schema.settle_transfer(&hash, &transfer, &hash);
+        schema.record_approval(&self.approver, amount);
+        Ok(())
+    }
+}
+
+impl RegisterApprover {
+    #[doc(hidden)]
+    pub fn sign(pk: &PublicKey, stake: u64, seed: u64, sk: &SecretKey) -> Signed<RawTransaction> {
+        Message::sign_transaction(Self { stake, seed }, CRYPTOCURRENCY_SERVICE_ID, *pk, sk)
+    }
+}
+
+impl Execute for RegisterApprover {
+    fn execute(&self, mut context: ExecutionContext) -> ExecutionResult {
+        let pub_key = &context.author();
+        let hash = context.tx_hash();
+        let stake = self.stake;
+
+        let mut schema = Schema::new(context.fork());
+
+        if schema.approver(pub_key).is_some() {
+            Err(Error::ApproverAlreadyRegistered)?;
+        }
+
+        if stake < MIN_APPROVER_STAKE {
+            Err(Error::StakeTooLow {
+                stake,
+                minimum: MIN_APPROVER_STAKE,
+            })?;
+        }
+
+        let wallet = schema.wallet(pub_key)
+            .ok_or(Error::WalletNotFound)?;
+
+        if wallet.balance < stake {
+            Err(Error::InsufficientCurrencyAmount {
+                needed: stake,
+                available: wallet.balance,
+            })?;
+        }
+
+        schema.register_approver(wallet, stake, &hash);
+        Ok(())
+    }
+}
+
+impl SlashApprover {
+    #[doc(hidden)]
+    pub fn sign(
+        pk: &PublicKey,
+        &approver: &PublicKey,
+        amount: u64,
+        seed: u64,
+        sk: &SecretKey,
+    ) -> Signed<RawTransaction> {
+        Message::sign_transaction(
+            Self { approver, amount, seed },
+            CRYPTOCURRENCY_SERVICE_ID,
+            *pk,
+            sk,
+        )
+    }
+}
+
+impl Execute for SlashApprover {
+    fn execute(&self, mut context: ExecutionContext) -> ExecutionResult {
+        let author = &context.author();
+
+        let mut schema = Schema::new(context.fork());
+
+        if !schema.is_validator(author) {
+            Err(Error::NotValidator)?;
+        }
+
+        if self.amount == 0 {
+            Err(Error::ZeroSlashAmount)?;
+        }
+
+        let approver = schema.approver(&self.approver)
+            .ok_or(Error::ApproverNotRegistered)?;
+
+        let amount = self.amount.min(approver.stake);
+        schema.slash_approver(approver, amount);
+        Ok(())
+    }
+}
+
+impl Reindex {
+    #[doc(hidden)]
+    pub fn sign(pk: &PublicKey, seed: u64, sk: &SecretKey) -> Signed<RawTransaction> {
+        Message::sign_transaction(Self { seed }, CRYPTOCURRENCY_SERVICE_ID, *pk, sk)
+    }
+}
+
+impl Execute for Reindex {
+    fn execute(&self, mut context: ExecutionContext) -> ExecutionResult {
+        let author = &context.author();
+
+        let mut schema = Schema::new(context.fork());
+
+        if !schema.is_validator(author) {
+            Err(Error::NotValidator)?;
+        }
+
+        schema.reindex();
+        Ok(())
+    }
+}
+
+impl UnregisterApprover {
+    #[doc(hidden)]
+    pub fn sign(pk: &PublicKey, seed: u64, sk: &SecretKey) -> Signed<RawTransaction> {
+        Message::sign_transaction(Self { seed }, CRYPTOCURRENCY_SERVICE_ID, *pk, sk)
+    }
+}
+
+impl Execute for UnregisterApprover {
+    fn execute(&self, mut context: ExecutionContext) -> ExecutionResult {
+        let pub_key = &context.author();
+        let hash = context.tx_hash();
+
+        let mut schema = Schema::new(context.fork());
+
+        let approver = schema.approver(pub_key)
+            .ok_or(Error::ApproverNotRegistered)?;
+
+        if schema.pending_transfers(TransferParty::Approver, pub_key).iter().next().is_some() {
+            Err(Error::ApproverHasPendingTransfers)?;
+        }
+
+        let wallet = schema.wallet(pub_key)
+            .ok_or(Error::WalletNotFound)?;
+
+        schema.unregister_approver(approver, wallet, &hash);
+        Ok(())
+    }
+}
@@ -0,0 +1,235 @@
+use std::collections::BTreeSet;
+
+use exonum::{
+    blockchain::{Block, Blockchain},
+    crypto::{CryptoHash, PublicKey},
+    helpers::Height,
+    messages::{Precommit, Signed},
+    storage::{ListProofError, MapProofError},
+};
+use failure::Fail;
+use crate::{
+    api::{WalletHistory, WalletInfo},
+    history::HistoryEntry,
+    schema::WALLETS_TABLE,
+    wallet::Wallet,
+    CRYPTOCURRENCY_SERVICE_ID,
+};
+
+/// Errors of the verification of the wallet information, one for each link of the
+/// chain from the validators' precommits down to the wallet history.
+#[derive(Debug, Fail)]
+pub enum Error {
+    /// Precommit is signed by a key which is not the key of its validator.
+    #[fail(display = "Precommit of validator {} is signed by an unknown key", validator)]
+    UnknownValidator {
+        /// Identifier of the validator in the precommit.
+        validator: u16,
+    },
+
+    /// Precommit is made for another block.
+    #[fail(display = "Precommit of validator {} is made for another block", validator)]
+    PrecommitMismatch {
+        /// Identifier of the validator in the precommit.
+        validator: u16,
+    },
+
+    /// Block is not signed by a supermajority of the validators.
+    #[fail(
+        display = "Block is signed by {} validators, {} required",
+        signed, required
+    )]
+    InsufficientPrecommits {
+        /// Number of the distinct validators which signed the block.
+        signed: usize,
+        /// Number of the validators required to sign the block.
+        required: usize,
+    },
+
+    /// Proof of the service table is malformed.
+    #[fail(display = "Invalid proof of the service table: {}", _0)]
+    InvalidTableProof(#[cause] MapProofError),
+
+    /// Proof of the service table doesn't lead to the state hash of the block.
+    #[fail(display = "Proof of the service table doesn't match the block state hash")]
+    TableRootMismatch,
+
+    /// Proof of the service table doesn't contain the wallets table.
+    #[fail(display = "Proof of the service table doesn't contain the wallets table")]
+    TableNotFound,
+
+    /// Proof of the wallet is malformed.
+    #[fail(display = "Invalid proof of the wallet: {}", _0)]
+    InvalidWalletProof(#[cause] MapProofError),
+
+    /// Proof of the wallet doesn't lead to the hash of the wallets table.
+    #[fail(display = "Proof of the wallet doesn't match the wallets table hash")]
+    WalletRootMismatch,
+
+    /// Proof of the wallet doesn't mention the requested wallet.
+    #[fail(display = "Proof of the wallet doesn't mention the requested wallet")]
+    WalletNotProved,
+
+    /// Wallet is proved to be absent.
+    #[fail(display = "Wallet doesn't exist")]
+    WalletNotFound,
+
+    /// Proof of the wallet history is malformed or doesn't match the wallet history hash.
+    #[fail(display = "Invalid proof of the wallet history: {}", _0)]
+    InvalidHistoryProof(#[cause] ListProofError),
+
+    /// Proof of the wallet history doesn't contain all entries of the history.
+    #[fail(
+        display = "Wallet history contains {} entries, {} expected",
+        proved, expected
+    )]
+    IncompleteHistory {
+        /// Number of the proved history entries.
+        proved: u64,
+        /// Length of the wallet history.
+        expected: u64,
+    },
+
+    /// Number of the transactions differs from the number of the history entries.
+    #[fail(
+        display = "Wallet history contains {} transactions, {} received",
+        expected, received
+    )]
+    TransactionCountMismatch {
+        /// Number of the history entries which are not checkpoints.
+        expected: usize,
+        /// Number of the received transactions.
+        received: usize,
+    },
+
+    /// Transaction doesn't correspond to its history entry.
+    #[fail(display = "Transaction {} doesn't match its history entry", index)]
+    TransactionMismatch {
+        /// Index of the transaction among the received ones.
+        index: usize,
+    },
+}
+
+/// Wallet information verified down from the validators' precommits.
+#[derive(Debug, Clone)]
+pub struct VerifiedWallet {
+    /// Height of the block in which the wallet was proved.
+    pub height: Height,
+    /// The wallet.
+    pub wallet: Wallet,
+    /// History of the wallet, `None` if it was omitted from the response.
+    pub history: Option<Vec<HistoryEntry>>,
+}
+
+/// Verify the response of the `wallets/info` endpoint for the wallet with the given key.
+///
+/// `validators` are the consensus public keys of the validators ordered by their
+/// identifiers. The block must be signed by more than two thirds of them.
+pub fn verify_wallet_info(
+    info: &WalletInfo,
+    pub_key: &PublicKey,
+    validators: &[PublicKey],
+) -> Result<VerifiedWallet, Error> {
+    let block = &info.block_proof.block;
+    verify_precommits(block, &info.block_proof.precommits, validators)?;
+
+    let to_table = info
+        .wallet_proof
+        .to_table
+        .check()
+        .map_err(Error::InvalidTableProof)?;
+    if to_table.merkle_root() != *block.state_hash() {
+        Err(Error::TableRootMismatch)?;
+    }
+    let table_key = Blockchain::service_table_unique_key(CRYPTOCURRENCY_SERVICE_ID, WALLETS_TABLE);
+    let (_, &wallets_hash) = to_table
+        .entries()
+        .find(|(&key, _)| key == table_key)
+        .ok_or(Error::TableNotFound)?;
+
+    let to_wallet = info
+        .wallet_proof
+        .to_wallet
+        .check()
+        .map_err(Error::InvalidWalletProof)?;
+    if to_wallet.merkle_root() != wallets_hash {
+        Err(Error::WalletRootMismatch)?;
+    }
+    let (_, wallet) = to_wallet
+        .all_entries()
+        .find(|(&key, _)| key == *pub_key)
+        .ok_or(Error::WalletNotProved)?;
+    let wallet = wallet.cloned().ok_or(Error::WalletNotFound)?;
+
+    let history = match info.wallet_history {
+        Some(ref history) => Some(verify_history(history, &wallet)?),
+        None => None,
+    };
+
+    Ok(VerifiedWallet {
+        height: block.height(),
+        wallet,
+        history,
+    })
+}
+
+/// Check that the block is signed by a supermajority of the given validators.
+fn verify_precommits(
+    block: &Block,
+    precommits: &[Signed<Precommit>],
+    validators: &[PublicKey],
+) -> Result<(), Error> {
+    let block_hash = block.hash();
+    let mut signed = BTreeSet::new();
+    for precommit in precommits {
+        let validator = precommit.validator().0;
+        if validators.get(validator as usize) != Some(&precommit.author()) {
+            Err(Error::UnknownValidator { validator })?;
+        }
+        if *precommit.block_hash() != block_hash || precommit.height() != block.height() {
+            Err(Error::PrecommitMismatch { validator })?;
+        }
+        signed.insert(validator);
+    }
+
+    let required = validators.len() * 2 / 3 + 1;
+    if signed.len() < required {
+        Err(Error::InsufficientPrecommits {
+            signed: signed.len(),
+            required,
+        })?;
+    }
+    Ok(())
+}
+
+/// Check the history of the wallet and its transactions.
+fn verify_history(history: &WalletHistory, wallet: &Wallet) -> Result<Vec<HistoryEntry>, Error> {
+    let entries = history
+        .proof
+        .validate(wallet.history_hash, wallet.history_len)
+        .map_err(Error::InvalidHistoryProof)?;
+    if entries.len() as u64 != wallet.history_len {
+        Err(Error::IncompleteHistory {
+            proved: entries.len() as u64,
+            expected: wallet.history_len,
+        })?;
+    }
+
+    let entries = entries
+        .into_iter()
+        .map(|(_, entry)| entry.clone())
+        .collect::<Vec<_>>();
+    let transactions = entries.iter().filter(|entry| !entry.is_checkpoint()).collect::<Vec<_>>();
+    if transactions.len() != history.transactions.len() {
+        Err(Error::TransactionCountMismatch {
+            expected: transactions.len(),
+            received: history.transactions.len(),
+        })?;
+    }
+    for (index, (entry, tx)) in transactions.iter().zip(&history.transactions).enumerate() {
+        if tx.signed_message().hash() != entry.tx_hash {
+            Err(Error::TransactionMismatch { index })?;
+        }
+    }
+    Ok(entries)
+}
@@ -1,7 +1,35 @@
-use exonum::crypto::{Hash, PublicKey};
+use exonum::crypto::{self, Hash, PublicKey};
 use exonum_derive::ProtobufConvert;
 use crate::proto;
 
+/// Returns the normalized form of the wallet name.
+///
+/// Names are unique up to the case and the surrounding whitespace.
+pub fn normalize_name(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+/// Returns the key of the wallet name in the name registry.
+pub fn name_key(name: &str) -> Hash {
+    crypto::hash(normalize_name(name).as_bytes())
+}
+
+/// Maximum number of the wallets in the page of the wallet listing.
+pub const MAX_WALLETS_PAGE_LEN: u64 = 100;
+/// Maximum number of the wallets examined by the wallet listing in one request.
+pub const MAX_WALLETS_SCAN: u64 = 1000;
+/// Maximum number of the wallets in one batch query.
+pub const MAX_WALLETS_BATCH_LEN: u64 = 100;
+
+/// Returns the key of the wallet in the index of the wallets ordered by balance.
+///
+/// Keys of the richer wallets precede the keys of the poorer ones.
+pub fn balance_key(balance: u64, pub_key: &PublicKey) -> Vec<u8> {
+    let mut key = (u64::max_value() - balance).to_be_bytes().to_vec();
+    key.extend_from_slice(pub_key.as_ref());
+    key
+}
+
 /// Wallet information stored in the database.
 #[derive(Clone, Debug, ProtobufConvert)]
 #[exonum(pb = "proto::Wallet", serde_pb_convert)]
@@ -14,9 +42,9 @@ pub struct Wallet {
     pub balance: u64,
     /// The amount is retained until the transaction is confirmed.
     pub retained_amount: u64,
-    /// Length of the transactions history.
+    /// Length of the transactions history since the last checkpoint.
     pub history_len: u64,
-    /// `Hash` of the transactions history.
+    /// `Hash` of the transactions history since the last checkpoint.
     pub history_hash: Hash,
 }
 
@@ -40,30 +68,6 @@ impl Wallet {
         }
     }
 
-    /// Returns a copy of this wallet with updated balance.
-    pub fn set_balance(self, balance: u64, history_hash: Hash) -> Self {
-        Self::new(
-            self.pub_key,
-            &self.name,
-            balance,
-            self.retained_amount,
-            self.history_len + 1,
-            history_hash,
-        )
-    }
-
-    /// Returns a copy of this wallet with updated retained amount.
-    pub fn set_retained_amount(self, amount: u64, history_hash: Hash) -> Self {
-        Self::new(
-            self.pub_key,
-            &self.name,
-            self.balance,
-            amount,
-            self.history_len + 1,
-            history_hash,
-        )
-    }
-
     /// Returns a copy of this wallet with updated balance and retained amount.
     pub fn set_balance_and_retained_amount(
         self,
@@ -80,4 +84,4 @@ impl Wallet {
             history_hash,
         )
     }
-}
\ No newline at end of file
+}
@@ -1,16 +1,54 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use assert_matches::assert_matches;
 use serde_json::json;
 use exonum::{
     api::node::public::explorer::{TransactionQuery, TransactionResponse},
     crypto::{self, Hash, PublicKey, SecretKey},
+    helpers::Height,
     messages::{self, RawTransaction, Signed},
+    storage::{ProofListIndex, ProofMapIndex},
 };
 use exonum_testkit::{ApiKind, TestKit, TestKitApi, TestKitBuilder};
 
 // Import data types used in tests from the crate where the service is defined.
 use cryptocurrency_advanced::{
-    api::{WalletInfo, WalletQuery},
-    transactions::{CreateWallet, Transfer, Approve},
-    wallet::Wallet,
+    api::{
+        WalletsBatchInfo, WalletsBatchQuery, SimulationQuery, SimulationResult, WalletDelta, SignedTransactionQuery, SubmittedTransaction, UnsignedTransaction, UnsignedTransactionQuery,
+        EventsPage, EventsQuery, ConsistencyReport, ReindexQuery, ReindexResponse, RolesInfo, ServiceConfig,
+        ArchivedBalanceProof, ArchivedHistoryInfo, ArchivedHistoryQuery, BalanceAtInfo, BalanceAtQuery, StatisticsInfo,
+        StatisticsQuery, PendingApproval, PendingApprovalsInfo, PendingApprovalsQuery, PendingTransfersInfo, PendingTransfersQuery, WalletHistoryInfo, WalletHistoryQuery, WalletInfoQuery, WalletsPage, WalletsQuery, RichlistPage,
+        RichlistQuery, ApproverInfo, AttestationInfo, AttestationQuery, SettledTransferInfo, TransferQuery, TransferStatusInfo, InvoiceInfo, InvoiceQuery, NameInfo, NameQuery, ReverseNameInfo, WalletInfo, WalletQuery,
+    },
+    approver::{Approver, APPROVER_STATUS_ACTIVE, APPROVER_STATUS_SLASHED},
+    event::{
+        EVENT_HISTORY_ENTRY, EVENT_TRANSFER_AWAITING_APPROVAL, EVENT_TRANSFER_SETTLED,
+        MAX_EVENTS_LOG_LEN, MAX_EVENTS_PAGE_LEN,
+    },
+    history::{
+        self, HistoryEntry, HISTORY_COMPACTION_THRESHOLD, KIND_APPROVE, KIND_CREATE_WALLET,
+        KIND_ISSUE, KIND_TRANSFER, KIND_UNREGISTER_APPROVER,
+    },
+    invoice::{
+        Invoice, INVOICE_STATUS_EXPIRED, INVOICE_STATUS_OPEN, INVOICE_STATUS_PAID,
+        INVOICE_STATUS_PAYMENT_PENDING,
+    },
+    oracle::Condition,
+    proto,
+    schema::{Schema, TransferParty, SCHEMA_VERSION},
+    sender_filter::{FILTER_MODE_ALLOWLIST, FILTER_MODE_BLOCKLIST},
+    settlement::{
+        TransferStatus, TRANSFER_STATUS_FILTERED, TRANSFER_STATUS_PENDING, TRANSFER_STATUS_REJECTED,
+        TRANSFER_STATUS_SETTLED,
+    },
+    statistics::BlockStatistics,
+    transactions::{
+        CreateWallet, Issue, Transfer, Approve, SetSenderFilter, UpdateSenderList, CreateInvoice,
+        PayInvoice, Refund, Attest, TransferWithApproval, RegisterApprover, SlashApprover, Reindex,
+        UnregisterApprover, ErrorInfo, Error, error_catalogue, WalletTransactions,
+    },
+    verify::{self, verify_wallet_info},
+    wallet::{self, Wallet},
     Service,
 };
 
@@ -30,6 +68,63 @@ fn test_create_wallet() {
     assert_eq!(wallet.balance, 100);
 }
 
+/// Check that wallet names are unique up to the case and surrounding whitespace.
+#[test]
+fn test_create_wallet_duplicate_name() {
+    let (mut testkit, api) = create_testkit();
+    let (tx_alice, _) = api.create_wallet("Alice");
+    testkit.create_block();
+    api.assert_tx_status(tx_alice.hash(), &json!({ "type": "success" }));
+
+    let (tx, _) = api.create_wallet(" alice ");
+    testkit.create_block();
+    api.assert_tx_status(
+        tx.hash(),
+        &json!({ "type": "error", "code": 13, "description": "Wallet name is already taken" }),
+    );
+    api.assert_no_wallet(tx.author());
+}
+
+/// Check that wallet names are resolved to public keys and back.
+#[test]
+fn test_resolve_name() {
+    let (mut testkit, api) = create_testkit();
+    let (tx_alice, _) = api.create_wallet("Alice");
+    testkit.create_block();
+
+    let name_info: NameInfo = api
+        .inner
+        .public(ApiKind::Service("cryptocurrency"))
+        .query(&NameQuery { name: "ALICE".to_owned() })
+        .get("v1/names/resolve")
+        .unwrap();
+    let to_name = name_info.name_proof.to_name.check().unwrap();
+    let (_, pub_key) = to_name.entries().next().unwrap();
+    assert_eq!(*pub_key, tx_alice.author());
+
+    let name_info: NameInfo = api
+        .inner
+        .public(ApiKind::Service("cryptocurrency"))
+        .query(&NameQuery { name: "Bob".to_owned() })
+        .get("v1/names/resolve")
+        .unwrap();
+    let to_name = name_info.name_proof.to_name.check().unwrap();
+    assert_eq!(to_name.entries().count(), 0);
+
+    let reverse_info: ReverseNameInfo = api
+        .inner
+        .public(ApiKind::Service("cryptocurrency"))
+        .query(&WalletQuery { pub_key: tx_alice.author() })
+        .get("v1/names/reverse")
+        .unwrap();
+    let to_wallet = reverse_info.wallet_proof.to_wallet.check().unwrap();
+    let (_, wallet) = to_wallet.entries().next().unwrap();
+    assert_eq!(wallet.name, "Alice");
+    let to_name = reverse_info.name_proof.unwrap().to_name.check().unwrap();
+    let (_, pub_key) = to_name.entries().next().unwrap();
+    assert_eq!(*pub_key, tx_alice.author());
+}
+
 /// Check that the transfer transaction works as intended.
 #[test]
 fn test_transfer() {
@@ -41,8 +136,8 @@ fn test_transfer() {
     api.assert_tx_status(tx_alice.hash(), &json!({ "type": "success" }));
     api.assert_tx_status(tx_bob.hash(), &json!({ "type": "success" }));
 
-    // Create approver's keys
-    let (approver_pk, approver_sk) = crypto::gen_keypair();
+    // Register the approver.
+    let (approver_pk, approver_sk) = api.create_approver(&mut testkit);
 
     // Check that the initial Alice's and Bob's balances persisted by the service.
     let wallet = api.get_wallet(tx_alice.author()).unwrap();
@@ -95,10 +190,98 @@ fn test_transfer() {
     assert_eq!(wallet.retained_amount, 0);
 }
 
+/// Check that a transfer encoded without the condition, as by the clients predating
+/// the conditional transfers, is an approved transfer and keeps its encoding.
+#[test]
+fn test_transfer_without_condition_encoding() {
+    use exonum::proto::ProtobufConvert;
+    use protobuf::Message;
+
+    let (from, _) = crypto::gen_keypair();
+    let (to, _) = crypto::gen_keypair();
+    let (approver, _) = crypto::gen_keypair();
+
+    let mut pb = proto::Transfer::new();
+    pb.set_from(from.to_pb());
+    pb.set_to(to.to_pb());
+    pb.set_approver(approver.to_pb());
+    pb.set_amount(10);
+    pb.set_seed(0);
+    let bytes = pb.write_to_bytes().unwrap();
+
+    let transfer = Transfer::from_pb(pb).unwrap();
+    assert!(!transfer.is_conditional());
+    assert_eq!(transfer.condition, Condition::none());
+    assert_eq!(transfer.to_pb().write_to_bytes().unwrap(), bytes);
+}
+
+/// Check that a transfer with the pre-signed approval is settled immediately.
+#[test]
+fn test_transfer_with_approval() {
+    let (mut testkit, api) = create_testkit();
+    let (tx_alice, key_alice) = api.create_wallet("Alice");
+    let (tx_bob, _) = api.create_wallet("Bob");
+    testkit.create_block();
+
+    let (approver_pk, approver_sk) = api.create_approver(&mut testkit);
+
+    // The approver signs the transfer contents off-chain.
+    let transfer = Transfer {
+        from: tx_alice.author(),
+        to: tx_bob.author(),
+        approver: approver_pk,
+        amount: 10,
+        seed: 0,
+        condition: Condition::none(),
+        outcome: false,
+    };
+    let approval = transfer.sign_approval(&approver_sk);
+
+    let tx = TransferWithApproval::sign(
+        &tx_alice.author(),
+        &tx_bob.author(),
+        &approver_pk,
+        10, // transferred amount
+        0,  // seed
+        &approval,
+        &key_alice,
+    );
+    api.send_tx(&tx);
+    testkit.create_block();
+    api.assert_tx_status(tx.hash(), &json!({ "type": "success" }));
+
+    let wallet = api.get_wallet(tx_alice.author()).unwrap();
+    assert_eq!(wallet.balance, 90);
+    assert_eq!(wallet.retained_amount, 0);
+    let wallet = api.get_wallet(tx_bob.author()).unwrap();
+    assert_eq!(wallet.balance, 110);
+
+    // The approval doesn't match a transfer with another amount.
+    let tx = TransferWithApproval::sign(
+        &tx_alice.author(),
+        &tx_bob.author(),
+        &approver_pk,
+        20, // transferred amount
+        0,  // seed
+        &approval,
+        &key_alice,
+    );
+    api.send_tx(&tx);
+    testkit.create_block();
+    api.assert_tx_status(
+        tx.hash(),
+        &json!({ "type": "error", "code": 23, "description": "Approver's signature doesn't match the transfer" }),
+    );
+
+    let wallet = api.get_wallet(tx_alice.author()).unwrap();
+    assert_eq!(wallet.balance, 90);
+}
+
 /// Check that a transfer from a non-existing wallet fails as expected.
 #[test]
 fn test_transfer_from_nonexisting_wallet() {
     let (mut testkit, api) = create_testkit();
+    let (approver_pk, _) = api.create_approver(&mut testkit);
 
     let (tx_alice, key_alice) = api.create_wallet("Alice");
     let (tx_bob, _) = api.create_wallet("Bob");
@@ -111,9 +294,6 @@ fn test_transfer_from_nonexisting_wallet() {
     assert_eq!(wallet.balance, 100);
     assert_eq!(wallet.retained_amount, 0);
 
-    // Create approver's pub key
-    let (approver_pk, _) = crypto::gen_keypair();
-
     let tx = Transfer::sign(
         &tx_alice.author(),
         &tx_bob.author(),
@@ -139,6 +319,7 @@ fn test_transfer_from_nonexisting_wallet() {
 #[test]
 fn test_transfer_to_nonexisting_wallet() {
     let (mut testkit, api) = create_testkit();
+    let (approver_pk, _) = api.create_approver(&mut testkit);
 
     let (tx_alice, key_alice) = api.create_wallet("Alice");
     let (tx_bob, _) = api.create_wallet("Bob");
@@ -151,9 +332,6 @@ fn test_transfer_to_nonexisting_wallet() {
     assert_eq!(wallet.retained_amount, 0);
     api.assert_no_wallet(tx_bob.author());
 
-    // Create approver's pub key
-    let (approver_pk, _) = crypto::gen_keypair();
-
     let tx = Transfer::sign(
         &tx_alice.author(),
         &tx_bob.author(),
@@ -184,8 +362,8 @@ fn test_transfer_overcharge() {
     let (tx_bob, _) = api.create_wallet("Bob");
     testkit.create_block();
 
-    // Create approver's pub key
-    let (approver_pk, _) = crypto::gen_keypair();
+    // Register the approver.
+    let (approver_pk, _) = api.create_approver(&mut testkit);
 
     // Transfer funds. The transfer amount (110) is more than Alice has (100).
     let tx = Transfer::sign(
@@ -200,7 +378,11 @@ fn test_transfer_overcharge() {
     testkit.create_block();
     api.assert_tx_status(
         tx.hash(),
-        &json!({ "type": "error", "code": 3, "description": "Insufficient currency amount" }),
+        &json!({
+            "type": "error",
+            "code": 3,
+            "description": "Insufficient currency amount: needed 110, available 100",
+        }),
     );
 
     let wallet = api.get_wallet(tx_alice.author()).unwrap();
@@ -226,8 +408,8 @@ fn test_approve_nonexisting_transfer() {
     assert_eq!(wallet.balance, 100);
     assert_eq!(wallet.retained_amount, 0);
 
-    // Create approver's keys
-    let (approver_pk, approver_sk) = crypto::gen_keypair();
+    // Register the approver.
+    let (approver_pk, approver_sk) = api.create_approver(&mut testkit);
 
     let tx = Transfer::sign(
         &tx_alice.author(),
@@ -278,8 +460,8 @@ fn test_double_approve_transfer() {
     assert_eq!(wallet.balance, 100);
     assert_eq!(wallet.retained_amount, 0);
 
-    // Create approver's keys
-    let (approver_pk, approver_sk) = crypto::gen_keypair();
+    // Register the approver.
+    let (approver_pk, approver_sk) = api.create_approver(&mut testkit);
 
     // Transfer funds
     let tx = Transfer::sign(
@@ -397,8 +579,8 @@ fn test_wrong_approver() {
     let (tx_bob, _) = api.create_wallet("Bob");
     testkit.create_block();
 
-    // Create approver's pub key
-    let (approver_pk, _) = crypto::gen_keypair();
+    // Register the approver.
+    let (approver_pk, _) = api.create_approver(&mut testkit);
 
     // Transfer funds.
     let tx = Transfer::sign(
@@ -440,117 +622,2475 @@ fn test_wrong_approver() {
     assert_eq!(wallet.retained_amount, 0);
 }
 
+/// Check that a receiver with an allowlist accepts funds only from the listed senders.
 #[test]
-fn test_unknown_wallet_request() {
-    let (_testkit, api) = create_testkit();
+fn test_sender_allowlist() {
+    let (mut testkit, api) = create_testkit();
 
-    // Transaction is sent by API, but isn't committed.
-    let (tx, _) = api.create_wallet("Alice");
+    let (tx_alice, key_alice) = api.create_wallet("Alice");
+    let (tx_bob, key_bob) = api.create_wallet("Bob");
+    let (tx_carol, key_carol) = api.create_wallet("Carol");
+    testkit.create_block();
 
-    api.assert_no_wallet(tx.author());
-}
+    // Bob accepts funds from Carol only.
+    let tx = SetSenderFilter::sign(&tx_bob.author(), FILTER_MODE_ALLOWLIST, 0, &key_bob);
+    api.send_tx(&tx);
+    let tx = UpdateSenderList::sign(&tx_bob.author(), &tx_carol.author(), true, 0, &key_bob);
+    api.send_tx(&tx);
+    testkit.create_block();
+    api.assert_tx_status(tx.hash(), &json!({ "type": "success" }));
 
-/// Wrapper for the cryptocurrency service API allowing to easily use it
-/// (compared to `TestKitApi` calls).
-struct CryptocurrencyApi {
-    pub inner: TestKitApi,
+    let (approver_pk, _) = api.create_approver(&mut testkit);
+
+    let tx = Transfer::sign(
+        &tx_alice.author(),
+        &tx_bob.author(),
+        &approver_pk,
+        10, // transfer amount
+        0,  // seed
+        &key_alice,
+    );
+    api.transfer(&tx);
+    testkit.create_block();
+    api.assert_tx_status(
+        tx.hash(),
+        &json!({ "type": "error", "code": 8, "description": "Receiver doesn't accept funds from the sender" }),
+    );
+
+    let tx = Transfer::sign(
+        &tx_carol.author(),
+        &tx_bob.author(),
+        &approver_pk,
+        10, // transfer amount
+        0,  // seed
+        &key_carol,
+    );
+    api.transfer(&tx);
+    testkit.create_block();
+    api.assert_tx_status(tx.hash(), &json!({ "type": "success" }));
+
+    let wallet = api.get_wallet(tx_alice.author()).unwrap();
+    assert_eq!(wallet.balance, 100);
+    assert_eq!(wallet.retained_amount, 0);
+    let wallet = api.get_wallet(tx_carol.author()).unwrap();
+    assert_eq!(wallet.balance, 90);
+    assert_eq!(wallet.retained_amount, 10);
 }
 
-impl CryptocurrencyApi {
-    /// Generates a wallet creation transaction with a random key pair, sends it over HTTP,
-    /// and checks the synchronous result (i.e., the hash of the transaction returned
-    /// within the response).
-    /// Note that the transaction is not immediately added to the blockchain, but rather is put
-    /// to the pool of unconfirmed transactions.
-    fn create_wallet(&self, name: &str) -> (Signed<RawTransaction>, SecretKey) {
-        let (pubkey, key) = crypto::gen_keypair();
-        // Create a pre-signed transaction
-        let tx = CreateWallet::sign(name, &pubkey, &key);
+/// Check that an approve returns the funds to the sender if the receiver blocked the sender
+/// after the transfer.
+#[test]
+fn test_sender_blocklist_on_approve() {
+    let (mut testkit, api) = create_testkit();
 
-        let data = messages::to_hex_string(&tx);
-        let tx_info: TransactionResponse = self
-            .inner
-            .public(ApiKind::Explorer)
-            .query(&json!({ "tx_body": data }))
-            .post("v1/transactions")
-            .unwrap();
-        assert_eq!(tx_info.tx_hash, tx.hash());
-        (tx, key)
-    }
+    let (tx_alice, key_alice) = api.create_wallet("Alice");
+    let (tx_bob, key_bob) = api.create_wallet("Bob");
+    testkit.create_block();
 
-    fn get_wallet(&self, pub_key: PublicKey) -> Option<Wallet> {
-        let wallet_info = self
-            .inner
-            .public(ApiKind::Service("cryptocurrency"))
-            .query(&WalletQuery { pub_key })
-            .get::<WalletInfo>("v1/wallets/info")
-            .unwrap();
+    let (approver_pk, approver_sk) = api.create_approver(&mut testkit);
 
-        let to_wallet = wallet_info.wallet_proof.to_wallet.check().unwrap();
-        let (_, wallet) = to_wallet.all_entries().find(|(&key, _)| key == pub_key)?;
-        wallet.cloned()
-    }
+    let tx = Transfer::sign(
+        &tx_alice.author(),
+        &tx_bob.author(),
+        &approver_pk,
+        10, // transfer amount
+        0,  // seed
+        &key_alice,
+    );
+    api.transfer(&tx);
+    testkit.create_block();
+    api.assert_tx_status(tx.hash(), &json!({ "type": "success" }));
 
-    /// Sends a transfer transaction over HTTP and checks the synchronous result.
-    fn transfer(&self, tx: &Signed<RawTransaction>) {
-        let data = messages::to_hex_string(&tx);
-        let tx_info: TransactionResponse = self
-            .inner
-            .public(ApiKind::Explorer)
-            .query(&json!({ "tx_body": data }))
-            .post("v1/transactions")
-            .unwrap();
-        assert_eq!(tx_info.tx_hash, tx.hash());
-    }
+    let transfer_tx_hash = tx.hash();
 
-    /// Sends a approve transaction over HTTP and checks the synchronous result.
-    fn approve(&self, tx: &Signed<RawTransaction>) {
-        let data = messages::to_hex_string(&tx);
-        let tx_info: TransactionResponse = self
-            .inner
-            .public(ApiKind::Explorer)
-            .query(&json!({ "tx_body": data }))
-            .post("v1/transactions")
-            .unwrap();
-        assert_eq!(tx_info.tx_hash, tx.hash());
-    }
+    // Bob blocks Alice before the transfer is approved.
+    let tx = SetSenderFilter::sign(&tx_bob.author(), FILTER_MODE_BLOCKLIST, 0, &key_bob);
+    api.send_tx(&tx);
+    let tx = UpdateSenderList::sign(&tx_bob.author(), &tx_alice.author(), true, 0, &key_bob);
+    api.send_tx(&tx);
+    testkit.create_block();
 
-    /// Asserts that a wallet with the specified public key is not known to the blockchain.
-    fn assert_no_wallet(&self, pub_key: PublicKey) {
-        let wallet_info: WalletInfo = self
-            .inner
-            .public(ApiKind::Service("cryptocurrency"))
-            .query(&WalletQuery { pub_key })
-            .get("v1/wallets/info")
-            .unwrap();
+    let tx = Approve::sign(
+        &approver_pk,
+        transfer_tx_hash,
+        0,  // seed
+        &approver_sk,
+    );
+    api.approve(&tx);
+    testkit.create_block();
+    api.assert_tx_status(tx.hash(), &json!({ "type": "success" }));
 
-        let to_wallet = wallet_info.wallet_proof.to_wallet.check().unwrap();
-        assert!(to_wallet.missing_keys().any(|&key| key == pub_key))
+    let wallet = api.get_wallet(tx_alice.author()).unwrap();
+    assert_eq!(wallet.balance, 100);
+    assert_eq!(wallet.retained_amount, 0);
+    let wallet = api.get_wallet(tx_bob.author()).unwrap();
+    assert_eq!(wallet.balance, 100);
+
+    let status = api.get_transfer_status(transfer_tx_hash).unwrap();
+    assert_eq!(status.status, TRANSFER_STATUS_FILTERED);
+    assert_eq!(status.resolution_tx_hash, tx.hash());
+}
+
+/// Check that a transfer to self fails with its own error code.
+#[test]
+fn test_transfer_to_self() {
+    let (mut testkit, api) = create_testkit();
+
+    let (tx_alice, key_alice) = api.create_wallet("Alice");
+    testkit.create_block();
+
+    let (approver_pk, _) = crypto::gen_keypair();
+
+    let tx = Transfer::sign(
+        &tx_alice.author(),
+        &tx_alice.author(),
+        &approver_pk,
+        10, // transfer amount
+        0,  // seed
+        &key_alice,
+    );
+    api.transfer(&tx);
+    testkit.create_block();
+    api.assert_tx_status(
+        tx.hash(),
+        &json!({ "type": "error", "code": 11, "description": "Sender same as receiver" }),
+    );
+}
+
+/// Check that the error catalogue lists every error under a distinct code.
+#[test]
+fn test_error_catalogue() {
+    let (_testkit, api) = create_testkit();
+
+    let errors: Vec<ErrorInfo> = api
+        .inner
+        .public(ApiKind::Service("cryptocurrency"))
+        .get("v1/errors")
+        .unwrap();
+
+    for (i, error) in errors.iter().enumerate() {
+        assert_eq!(error.code as usize, i);
     }
+    let insufficient = &errors[3];
+    assert_eq!(insufficient.name, "InsufficientCurrencyAmount");
+    assert_eq!(insufficient.details, vec!["needed", "available"]);
+}
 
-    /// Asserts that the transaction with the given hash has a specified status.
-    fn assert_tx_status(&self, tx_hash: Hash, expected_status: &serde_json::Value) {
-        let info: serde_json::Value = self
-            .inner
-            .public(ApiKind::Explorer)
-            .query(&TransactionQuery::new(tx_hash))
-            .get("v1/transactions")
-            .unwrap();
+/// Check that the code, name, description and details of every error match its
+/// entry in the error catalogue.
+#[test]
+fn test_error_catalogue_matches_errors() {
+    let errors = vec![
+        Error::WalletAlreadyExists,
+        Error::SenderNotFound,
+        Error::ReceiverNotFound,
+        Error::InsufficientCurrencyAmount { needed: 1, available: 2 },
+        Error::TransferNotFound,
+        Error::ApproverSameAsSender,
+        Error::ApproverSameAsReceiver,
+        Error::WrongApprover,
+        Error::SenderNotAccepted,
+        Error::UnknownSenderFilterMode { mode: 1 },
+        Error::WalletNotFound,
+        Error::SenderSameAsReceiver,
+        Error::WrongSender,
+        Error::NameAlreadyTaken,
+        Error::EmptyName,
+        Error::InvoiceNotFound,
+        Error::InvoiceAlreadyPaid,
+        Error::InvoiceExpired,
+        Error::DueHeightPassed { due_height: 1, current_height: 2 },
+        Error::NotTransferReceiver,
+        Error::RefundExceedsRemainder { requested: 1, remaining: 2 },
+        Error::ConditionalTransfer,
+        Error::AlreadyAttested,
+        Error::InvalidApproverSignature,
+        Error::ApproverNotRegistered,
+        Error::ApproverAlreadyRegistered,
+        Error::StakeTooLow { stake: 1, minimum: 2 },
+        Error::NotValidator,
+        Error::ZeroSlashAmount,
+        Error::ApproverHasPendingTransfers,
+    ];
+    // The exhaustive match breaks the build when an error is added without being listed above.
+    for error in &errors {
+        match error {
+            Error::WalletAlreadyExists
+            | Error::SenderNotFound
+            | Error::ReceiverNotFound
+            | Error::InsufficientCurrencyAmount { .. }
+            | Error::TransferNotFound
+            | Error::ApproverSameAsSender
+            | Error::ApproverSameAsReceiver
+            | Error::WrongApprover
+            | Error::SenderNotAccepted
+            | Error::UnknownSenderFilterMode { .. }
+            | Error::WalletNotFound
+            | Error::SenderSameAsReceiver
+            | Error::WrongSender
+            | Error::NameAlreadyTaken
+            | Error::EmptyName
+            | Error::InvoiceNotFound
+            | Error::InvoiceAlreadyPaid
+            | Error::InvoiceExpired
+            | Error::DueHeightPassed { .. }
+            | Error::NotTransferReceiver
+            | Error::RefundExceedsRemainder { .. }
+            | Error::ConditionalTransfer
+            | Error::AlreadyAttested
+            | Error::InvalidApproverSignature
+            | Error::ApproverNotRegistered
+            | Error::ApproverAlreadyRegistered
+            | Error::StakeTooLow { .. }
+            | Error::NotValidator
+            | Error::ZeroSlashAmount
+            | Error::ApproverHasPendingTransfers => {}
+        }
+    }
 
-        if let serde_json::Value::Object(mut info) = info {
-            let tx_status = info.remove("status").unwrap();
-            assert_eq!(tx_status, *expected_status);
+    let catalogue = error_catalogue();
+    assert_eq!(errors.len(), catalogue.len());
+    for error in &errors {
+        let info = catalogue
+            .iter()
+            .find(|info| info.code == error.code())
+            .unwrap_or_else(|| panic!("{:?} is missing from the catalogue", error));
+
+        // Debug output is the variant name followed by its fields, if any.
+        let debug = format!("{:?}", error);
+        let name = debug.split(' ').next().unwrap();
+        let details = debug
+            .split(' ')
+            .filter(|token| token.ends_with(':'))
+            .map(|token| token.trim_end_matches(':'))
+            .collect::<Vec<_>>();
+        assert_eq!(info.name, name);
+        assert_eq!(info.details, details);
+
+        let description = error.to_string();
+        if details.is_empty() {
+            assert_eq!(description, info.description);
         } else {
-            panic!("Invalid transaction info format, object expected");
+            assert!(description.starts_with(&format!("{}: ", info.description)));
         }
     }
 }
 
-/// Creates a testkit together with the API wrapper defined above.
-fn create_testkit() -> (TestKit, CryptocurrencyApi) {
-    let testkit = TestKitBuilder::validator().with_service(Service).create();
+/// Check that an invoice is paid with an approved transfer.
+#[test]
+fn test_pay_invoice() {
+    let (mut testkit, api) = create_testkit();
+
+    let (tx_alice, key_alice) = api.create_wallet("Alice");
+    let (tx_bob, key_bob) = api.create_wallet("Bob");
+    testkit.create_block();
+
+    let (approver_pk, approver_sk) = api.create_approver(&mut testkit);
+
+    // Alice issues an invoice to be paid until the block 10.
+    let tx = CreateInvoice::sign(
+        &tx_alice.author(),
+        30, // amount
+        10, // due height
+        &approver_pk,
+        crypto::hash(b"Order #1"),
+        0,  // seed
+        &key_alice,
+    );
+    api.send_tx(&tx);
+    testkit.create_block();
+    api.assert_tx_status(tx.hash(), &json!({ "type": "success" }));
+    let invoice_id = tx.hash();
+
+    // Bob pays the invoice.
+    let tx = PayInvoice::sign(&tx_bob.author(), invoice_id, 0, &key_bob);
+    api.send_tx(&tx);
+    testkit.create_block();
+    api.assert_tx_status(tx.hash(), &json!({ "type": "success" }));
+    let payment_tx_hash = tx.hash();
+
+    let invoice = api.get_invoice(invoice_id).unwrap();
+    assert_eq!(invoice.status, INVOICE_STATUS_PAYMENT_PENDING);
+    assert_eq!(invoice.payer, tx_bob.author());
+    assert_eq!(invoice.payment_tx_hash, payment_tx_hash);
+    let wallet = api.get_wallet(tx_bob.author()).unwrap();
+    assert_eq!(wallet.balance, 70);
+    assert_eq!(wallet.retained_amount, 30);
+
+    // The payment is approved as an ordinary transfer.
+    let tx = Approve::sign(&approver_pk, payment_tx_hash, 0, &approver_sk);
+    api.approve(&tx);
+    testkit.create_block();
+    api.assert_tx_status(tx.hash(), &json!({ "type": "success" }));
+    let wallet = api.get_wallet(tx_alice.author()).unwrap();
+    assert_eq!(wallet.balance, 130);
+    let invoice = api.get_invoice(invoice_id).unwrap();
+    assert_eq!(invoice.status, INVOICE_STATUS_PAID);
+    assert_eq!(invoice.payment_tx_hash, payment_tx_hash);
+
+    // The invoice can't be paid twice.
+    let tx = PayInvoice::sign(&tx_bob.author(), invoice_id, 1, &key_bob);
+    api.send_tx(&tx);
+    testkit.create_block();
+    api.assert_tx_status(
+        tx.hash(),
+        &json!({ "type": "error", "code": 16, "description": "Invoice is already paid" }),
+    );
+}
+
+/// Check that an invoice is reopened if its payment is rejected.
+#[test]
+fn test_pay_invoice_rejected() {
+    let (mut testkit, api) = create_testkit();
+
+    let (tx_alice, key_alice) = api.create_wallet("Alice");
+    let (tx_bob, key_bob) = api.create_wallet("Bob");
+    testkit.create_block();
+
+    let (approver_pk, approver_sk) = api.create_approver(&mut testkit);
+
+    let tx = CreateInvoice::sign(
+        &tx_alice.author(),
+        30, // amount
+        10, // due height
+        &approver_pk,
+        crypto::hash(b"Order #1"),
+        0,  // seed
+        &key_alice,
+    );
+    api.send_tx(&tx);
+    testkit.create_block();
+    let invoice_id = tx.hash();
+
+    let tx = PayInvoice::sign(&tx_bob.author(), invoice_id, 0, &key_bob);
+    api.send_tx(&tx);
+    testkit.create_block();
+    api.assert_tx_status(tx.hash(), &json!({ "type": "success" }));
+    let payment_tx_hash = tx.hash();
+
+    // Alice blocks Bob before the payment is approved, so the approval rejects it.
+    let tx = SetSenderFilter::sign(&tx_alice.author(), FILTER_MODE_BLOCKLIST, 0, &key_alice);
+    api.send_tx(&tx);
+    let tx = UpdateSenderList::sign(&tx_alice.author(), &tx_bob.author(), true, 0, &key_alice);
+    api.send_tx(&tx);
+    testkit.create_block();
+
+    let tx = Approve::sign(&approver_pk, payment_tx_hash, 0, &approver_sk);
+    api.approve(&tx);
+    testkit.create_block();
+    api.assert_tx_status(tx.hash(), &json!({ "type": "success" }));
+
+    let invoice = api.get_invoice(invoice_id).unwrap();
+    assert_eq!(invoice.status, INVOICE_STATUS_OPEN);
+    assert_eq!(invoice.payer, PublicKey::zero());
+    assert_eq!(invoice.payment_tx_hash, Hash::zero());
+    let wallet = api.get_wallet(tx_bob.author()).unwrap();
+    assert_eq!(wallet.balance, 100);
+    assert_eq!(wallet.retained_amount, 0);
+}
+
+/// Check that an invoice expires after its due height.
+#[test]
+fn test_invoice_expiration() {
+    let (mut testkit, api) = create_testkit();
+
+    let (tx_alice, key_alice) = api.create_wallet("Alice");
+    let (tx_bob, key_bob) = api.create_wallet("Bob");
+    testkit.create_block();
+
+    let (approver_pk, _) = api.create_approver(&mut testkit);
+
+    // The invoice is payable in the block 4 only.
+    let tx = CreateInvoice::sign(
+        &tx_alice.author(),
+        30, // amount
+        4,  // due height
+        &approver_pk,
+        crypto::hash(b"Order #1"),
+        0,  // seed
+        &key_alice,
+    );
+    api.send_tx(&tx);
+    testkit.create_block();
+    api.assert_tx_status(tx.hash(), &json!({ "type": "success" }));
+    let invoice_id = tx.hash();
+
+    let invoice = api.get_invoice(invoice_id).unwrap();
+    assert_eq!(invoice.status, INVOICE_STATUS_EXPIRED);
+
+    let tx = PayInvoice::sign(&tx_bob.author(), invoice_id, 0, &key_bob);
+    api.send_tx(&tx);
+    testkit.create_block();
+    api.assert_tx_status(
+        tx.hash(),
+        &json!({ "type": "error", "code": 17, "description": "Invoice is expired" }),
+    );
+
+    let wallet = api.get_wallet(tx_bob.author()).unwrap();
+    assert_eq!(wallet.balance, 100);
+    assert_eq!(wallet.retained_amount, 0);
+
+    // An invoice can't be due in the past.
+    let tx = CreateInvoice::sign(
+        &tx_alice.author(),
+        30, // amount
+        1,  // due height
+        &approver_pk,
+        crypto::hash(b"Order #2"),
+        0,  // seed
+        &key_alice,
+    );
+    api.send_tx(&tx);
+    testkit.create_block();
+    api.assert_tx_status(
+        tx.hash(),
+        &json!({
+            "type": "error",
+            "code": 18,
+            "description": "Due height is already passed: due height 1, current height 6",
+        }),
+    );
+}
+
+/// Check that the receiver can refund a settled transfer up to its amount.
+#[test]
+fn test_refund() {
+    let (mut testkit, api) = create_testkit();
+
+    let (tx_alice, key_alice) = api.create_wallet("Alice");
+    let (tx_bob, key_bob) = api.create_wallet("Bob");
+    testkit.create_block();
+
+    let (approver_pk, approver_sk) = api.create_approver(&mut testkit);
+
+    let tx = Transfer::sign(
+        &tx_alice.author(),
+        &tx_bob.author(),
+        &approver_pk,
+        30, // transfer amount
+        0,  // seed
+        &key_alice,
+    );
+    api.transfer(&tx);
+    testkit.create_block();
+    let transfer_tx_hash = tx.hash();
+
+    let tx = Approve::sign(&approver_pk, transfer_tx_hash, 0, &approver_sk);
+    api.approve(&tx);
+    testkit.create_block();
+    api.assert_tx_status(tx.hash(), &json!({ "type": "success" }));
+
+    // Only the receiver can refund the transfer.
+    let tx = Refund::sign(&tx_alice.author(), transfer_tx_hash, 10, 0, &key_alice);
+    api.send_tx(&tx);
+    testkit.create_block();
+    api.assert_tx_status(
+        tx.hash(),
+        &json!({ "type": "error", "code": 19, "description": "Only the receiver of the transfer can refund it" }),
+    );
+
+    let tx = Refund::sign(&tx_bob.author(), transfer_tx_hash, 20, 0, &key_bob);
+    api.send_tx(&tx);
+    testkit.create_block();
+    api.assert_tx_status(tx.hash(), &json!({ "type": "success" }));
+    let refund_tx_hash = tx.hash();
+
+    let wallet = api.get_wallet(tx_alice.author()).unwrap();
+    assert_eq!(wallet.balance, 90);
+    let wallet = api.get_wallet(tx_bob.author()).unwrap();
+    assert_eq!(wallet.balance, 110);
+
+    // The refund is capped at the not yet refunded remainder.
+    let tx = Refund::sign(&tx_bob.author(), transfer_tx_hash, 20, 1, &key_bob);
+    api.send_tx(&tx);
+    testkit.create_block();
+    api.assert_tx_status(
+        tx.hash(),
+        &json!({
+            "type": "error",
+            "code": 20,
+            "description": "Refund exceeds the not yet refunded amount: requested 20, remaining 10",
+        }),
+    );
+
+    let info: SettledTransferInfo = api
+        .inner
+        .public(ApiKind::Service("cryptocurrency"))
+        .query(&TransferQuery { hash: transfer_tx_hash })
+        .get("v1/transfers/settled")
+        .unwrap();
+    let to_transfer = info.transfer_proof.to_transfer.check().unwrap();
+    let (_, settled) = to_transfer.entries().next().unwrap();
+    assert_eq!(settled.refunded_amount, 20);
+    let refunds = info.refunds.unwrap().validate(settled.refunds_hash, 1).unwrap();
+    assert_eq!(*refunds[0].1, refund_tx_hash);
+}
+
+/// Check that conditional transfers are settled or returned by the oracle attestation.
+#[test]
+fn test_oracle_conditioned_transfers() {
+    let (mut testkit, api) = create_testkit();
+
+    let (tx_alice, key_alice) = api.create_wallet("Alice");
+    let (tx_bob, _) = api.create_wallet("Bob");
+    testkit.create_block();
+
+    let (oracle_pk, oracle_sk) = crypto::gen_keypair();
+    let condition = crypto::hash(b"Delivery #1");
+
+    // The first transfer is released if the delivery succeeds, the second one otherwise.
+    let tx_success = Transfer::sign_conditional(
+        &tx_alice.author(),
+        &tx_bob.author(),
+        &oracle_pk,
+        condition,
+        true, // outcome
+        10,   // transfer amount
+        0,    // seed
+        &key_alice,
+    );
+    api.transfer(&tx_success);
+    let tx_failure = Transfer::sign_conditional(
+        &tx_alice.author(),
+        &tx_bob.author(),
+        &oracle_pk,
+        condition,
+        false, // outcome
+        20,    // transfer amount
+        0,     // seed
+        &key_alice,
+    );
+    api.transfer(&tx_failure);
+    testkit.create_block();
+    api.assert_tx_status(tx_success.hash(), &json!({ "type": "success" }));
+    api.assert_tx_status(tx_failure.hash(), &json!({ "type": "success" }));
+
+    let wallet = api.get_wallet(tx_alice.author()).unwrap();
+    assert_eq!(wallet.balance, 70);
+    assert_eq!(wallet.retained_amount, 30);
+
+    // Conditional transfers can't be approved.
+    let tx = Approve::sign(&oracle_pk, tx_success.hash(), 0, &oracle_sk);
+    api.approve(&tx);
+    testkit.create_block();
+    api.assert_tx_status(
+        tx.hash(),
+        &json!({
+            "type": "error",
+            "code": 21,
+            "description": "Conditional transfer is settled by the oracle attestation only",
+        }),
+    );
+
+    let tx = Attest::sign(&oracle_pk, condition, true, 0, &oracle_sk);
+    api.send_tx(&tx);
+    testkit.create_block();
+    api.assert_tx_status(tx.hash(), &json!({ "type": "success" }));
+
+    let wallet = api.get_wallet(tx_alice.author()).unwrap();
+    assert_eq!(wallet.balance, 90);
+    assert_eq!(wallet.retained_amount, 0);
+    let wallet = api.get_wallet(tx_bob.author()).unwrap();
+    assert_eq!(wallet.balance, 110);
+
+    let info: AttestationInfo = api
+        .inner
+        .public(ApiKind::Service("cryptocurrency"))
+        .query(&AttestationQuery { oracle: oracle_pk, condition })
+        .get("v1/oracles/attestation")
+        .unwrap();
+    let to_attestation = info.attestation_proof.to_attestation.check().unwrap();
+    let (_, attestation) = to_attestation.entries().next().unwrap();
+    assert!(attestation.outcome);
+    assert_eq!(attestation.attest_tx_hash, tx.hash());
+
+    // The condition can be attested only once.
+    let tx = Attest::sign(&oracle_pk, condition, false, 1, &oracle_sk);
+    api.send_tx(&tx);
+    testkit.create_block();
+    api.assert_tx_status(
+        tx.hash(),
+        &json!({ "type": "error", "code": 22, "description": "Condition is already attested by the oracle" }),
+    );
+}
+
+/// Check that only registered approvers can be named in transfers.
+#[test]
+fn test_unregistered_approver() {
+    let (mut testkit, api) = create_testkit();
+
+    let (tx_alice, key_alice) = api.create_wallet("Alice");
+    let (tx_bob, _) = api.create_wallet("Bob");
+    testkit.create_block();
+
+    let (approver_pk, _) = crypto::gen_keypair();
+
+    let tx = Transfer::sign(
+        &tx_alice.author(),
+        &tx_bob.author(),
+        &approver_pk,
+        10, // transfer amount
+        0,  // seed
+        &key_alice,
+    );
+    api.transfer(&tx);
+    testkit.create_block();
+    api.assert_tx_status(
+        tx.hash(),
+        &json!({ "type": "error", "code": 24, "description": "Approver isn't registered or was slashed" }),
+    );
+
+    let wallet = api.get_wallet(tx_alice.author()).unwrap();
+    assert_eq!(wallet.balance, 100);
+    assert_eq!(wallet.retained_amount, 0);
+}
+
+/// Check that approvals are counted and that validators can slash approvers.
+#[test]
+fn test_approver_statistics_and_slashing() {
+    let (mut testkit, api) = create_testkit();
+
+    let (approver_pk, approver_sk) = api.create_approver(&mut testkit);
+    let wallet = api.get_wallet(approver_pk).unwrap();
+    assert_eq!(wallet.balance, 50);
+
+    let (tx_alice, key_alice) = api.create_wallet("Alice");
+    let (tx_bob, _) = api.create_wallet("Bob");
+    testkit.create_block();
+
+    let tx = Transfer::sign(
+        &tx_alice.author(),
+        &tx_bob.author(),
+        &approver_pk,
+        10, // transfer amount
+        0,  // seed
+        &key_alice,
+    );
+    api.transfer(&tx);
+    testkit.create_block();
+    let tx = Approve::sign(&approver_pk, tx.hash(), 0, &approver_sk);
+    api.approve(&tx);
+    testkit.create_block();
+    api.assert_tx_status(tx.hash(), &json!({ "type": "success" }));
+
+    let approver = api.get_approver(approver_pk).unwrap();
+    assert_eq!(approver.stake, 50);
+    assert_eq!(approver.approvals_count, 1);
+    assert_eq!(approver.approved_amount, 10);
+
+    // Only validators can slash approvers.
+    let tx = SlashApprover::sign(&tx_alice.author(), &approver_pk, 20, 0, &key_alice);
+    api.send_tx(&tx);
+    testkit.create_block();
+    api.assert_tx_status(
+        tx.hash(),
+        &json!({ "type": "error", "code": 27, "description": "Transaction author is not a validator" }),
+    );
+
+    let (validator_pk, validator_sk) = testkit.network().validators()[0].service_keypair();
+    let tx = SlashApprover::sign(validator_pk, &approver_pk, 0, 0, validator_sk);
+    api.send_tx(&tx);
+    testkit.create_block();
+    api.assert_tx_status(
+        tx.hash(),
+        &json!({ "type": "error", "code": 28, "description": "Slashed amount is zero" }),
+    );
+
+    // Approver stays active while the stake is not less than the minimum.
+    let tx = SlashApprover::sign(validator_pk, &approver_pk, 20, 1, validator_sk);
+    api.send_tx(&tx);
+    testkit.create_block();
+    api.assert_tx_status(tx.hash(), &json!({ "type": "success" }));
+
+    let approver = api.get_approver(approver_pk).unwrap();
+    assert_eq!(approver.stake, 30);
+    assert_eq!(approver.slashed_amount, 20);
+    assert_eq!(approver.status, APPROVER_STATUS_ACTIVE);
+
+    let tx = SlashApprover::sign(validator_pk, &approver_pk, 25, 2, validator_sk);
+    api.send_tx(&tx);
+    testkit.create_block();
+    api.assert_tx_status(tx.hash(), &json!({ "type": "success" }));
+
+    let approver = api.get_approver(approver_pk).unwrap();
+    assert_eq!(approver.stake, 5);
+    assert_eq!(approver.slashed_amount, 45);
+    assert_eq!(approver.status, APPROVER_STATUS_SLASHED);
+
+    // Slashed approver can't be named in new transfers.
+    let tx = Transfer::sign(
+        &tx_alice.author(),
+        &tx_bob.author(),
+        &approver_pk,
+        10, // transfer amount
+        1,  // seed
+        &key_alice,
+    );
+    api.transfer(&tx);
+    testkit.create_block();
+    api.assert_tx_status(
+        tx.hash(),
+        &json!({ "type": "error", "code": 24, "description": "Approver isn't registered or was slashed" }),
+    );
+}
+
+/// Check that approvers leave the registry with the rest of the stake once they have
+/// no pending transfers.
+#[test]
+fn test_unregister_approver() {
+    let (mut testkit, api) = create_testkit();
+
+    let (approver_pk, approver_sk) = api.create_approver(&mut testkit);
+    let (tx_alice, key_alice) = api.create_wallet("Alice");
+    let (tx_bob, _) = api.create_wallet("Bob");
+    testkit.create_block();
+
+    let tx_transfer = Transfer::sign(
+        &tx_alice.author(),
+        &tx_bob.author(),
+        &approver_pk,
+        10, // transfer amount
+        0,  // seed
+        &key_alice,
+    );
+    api.transfer(&tx_transfer);
+    testkit.create_block();
+
+    let tx = UnregisterApprover::sign(&approver_pk, 0, &approver_sk);
+    api.send_tx(&tx);
+    testkit.create_block();
+    api.assert_tx_status(
+        tx.hash(),
+        &json!({ "type": "error", "code": 29, "description": "Approver has pending transfers" }),
+    );
+
+    api.approve(&Approve::sign(&approver_pk, tx_transfer.hash(), 0, &approver_sk));
+    testkit.create_block();
+    let tx = UnregisterApprover::sign(&approver_pk, 1, &approver_sk);
+    api.send_tx(&tx);
+    testkit.create_block();
+    api.assert_tx_status(tx.hash(), &json!({ "type": "success" }));
+
+    assert!(api.get_approver(approver_pk).is_none());
+    let wallet = api.get_wallet(approver_pk).unwrap();
+    assert_eq!(wallet.balance, 100);
+    let history = api.get_wallet_history(approver_pk);
+    assert_eq!(history.last().unwrap().kind, KIND_UNREGISTER_APPROVER);
+    assert_eq!(history.last().unwrap().balance_delta, 50);
+
+    // The approver is registered no more.
+    let tx = UnregisterApprover::sign(&approver_pk, 2, &approver_sk);
+    api.send_tx(&tx);
+    let tx_transfer = Transfer::sign(
+        &tx_alice.author(),
+        &tx_bob.author(),
+        &approver_pk,
+        10, // transfer amount
+        1,  // seed
+        &key_alice,
+    );
+    api.transfer(&tx_transfer);
+    testkit.create_block();
+    api.assert_tx_status(
+        tx.hash(),
+        &json!({ "type": "error", "code": 24, "description": "Approver isn't registered or was slashed" }),
+    );
+    api.assert_tx_status(
+        tx_transfer.hash(),
+        &json!({ "type": "error", "code": 24, "description": "Approver isn't registered or was slashed" }),
+    );
+}
+
+/// Check that pending transfers are indexed by each party until approved.
+#[test]
+fn test_pending_transfers_indexes() {
+    let (mut testkit, api) = create_testkit();
+
+    let (approver_pk, approver_sk) = api.create_approver(&mut testkit);
+    let (tx_alice, key_alice) = api.create_wallet("Alice");
+    let (tx_bob, _) = api.create_wallet("Bob");
+    testkit.create_block();
+
+    let tx = Transfer::sign(
+        &tx_alice.author(),
+        &tx_bob.author(),
+        &approver_pk,
+        10, // transfer amount
+        0,  // seed
+        &key_alice,
+    );
+    api.transfer(&tx);
+    testkit.create_block();
+    let transfer_tx_hash = tx.hash();
+
+    for &(party, pub_key) in &[
+        (TransferParty::Sender, tx_alice.author()),
+        (TransferParty::Receiver, tx_bob.author()),
+        (TransferParty::Approver, approver_pk),
+    ] {
+        let transfers = api.get_pending_transfers(party, pub_key);
+        assert_eq!(transfers, vec![transfer_tx_hash]);
+    }
+    assert!(api.get_pending_transfers(TransferParty::Sender, tx_bob.author()).is_empty());
+
+    let tx = Approve::sign(&approver_pk, transfer_tx_hash, 0, &approver_sk);
+    api.approve(&tx);
+    testkit.create_block();
+    api.assert_tx_status(tx.hash(), &json!({ "type": "success" }));
+
+    assert!(api.get_pending_transfers(TransferParty::Approver, approver_pk).is_empty());
+    assert!(api.get_pending_transfers(TransferParty::Sender, tx_alice.author()).is_empty());
+}
+
+/// Check that the wallet history records typed entries with balance deltas.
+#[test]
+fn test_wallet_history_entries() {
+    let (mut testkit, api) = create_testkit();
+
+    let (approver_pk, approver_sk) = api.create_approver(&mut testkit);
+    let (tx_alice, key_alice) = api.create_wallet("Alice");
+    let (tx_bob, _) = api.create_wallet("Bob");
+    testkit.create_block();
+
+    let tx = Transfer::sign(
+        &tx_alice.author(),
+        &tx_bob.author(),
+        &approver_pk,
+        10, // transfer amount
+        0,  // seed
+        &key_alice,
+    );
+    api.transfer(&tx);
+    testkit.create_block();
+    let transfer_tx_hash = tx.hash();
+
+    let tx = Approve::sign(&approver_pk, transfer_tx_hash, 0, &approver_sk);
+    api.approve(&tx);
+    testkit.create_block();
+    api.assert_tx_status(tx.hash(), &json!({ "type": "success" }));
+
+    let history = api.get_wallet_history(tx_alice.author());
+    assert_eq!(history.len(), 3);
+    assert_eq!(history[0].kind, KIND_CREATE_WALLET);
+    assert_eq!(history[0].balance_delta, 100);
+    assert_eq!(history[1].tx_hash, transfer_tx_hash);
+    assert_eq!(history[1].kind, KIND_TRANSFER);
+    assert_eq!(history[1].counterparty, tx_bob.author());
+    assert_eq!((history[1].balance_delta, history[1].retained_delta), (-10, 10));
+    assert_eq!(history[1].balance, 90);
+    assert_eq!(history[2].kind, KIND_APPROVE);
+    assert_eq!((history[2].balance_delta, history[2].retained_delta), (0, -10));
+
+    let history = api.get_wallet_history(tx_bob.author());
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[1].kind, KIND_APPROVE);
+    assert_eq!(history[1].counterparty, tx_alice.author());
+    assert_eq!((history[1].balance_delta, history[1].retained_delta), (10, 0));
+    assert_eq!(history[1].balance, 110);
+}
+
+/// Check that a database written before the schema version was stored is upgraded
+/// when the next block is committed.
+#[test]
+fn test_schema_migration() {
+    let (mut testkit, api) = create_testkit();
+
+    let (approver_pk, _) = api.create_approver(&mut testkit);
+    let (tx_alice, key_alice) = api.create_wallet("Alice");
+    let (tx_bob, _) = api.create_wallet("Bob");
+    testkit.create_block();
+    assert_eq!(Schema::new(&testkit.snapshot()).schema_version(), SCHEMA_VERSION);
+
+    let tx = Transfer::sign(
+        &tx_alice.author(),
+        &tx_bob.author(),
+        &approver_pk,
+        10, // transfer amount
+        0,  // seed
+        &key_alice,
+    );
+    api.transfer(&tx);
+    testkit.create_block();
+
+    // Drop the version and the records missing in the unversioned databases.
+    let mut fork = testkit.blockchain_mut().fork();
+    {
+        let mut schema = Schema::new(&mut fork);
+        schema.schema_version_mut().remove();
+        schema.names_mut().remove(&wallet::name_key("Alice"));
+        schema
+            .pending_transfers_mut(TransferParty::Sender, &tx_alice.author())
+            .remove(&tx.hash());
+        schema
+            .pending_transfers_roots_mut(TransferParty::Sender)
+            .remove(&tx_alice.author());
+    }
+    testkit.blockchain_mut().merge(fork.into_patch()).unwrap();
+    assert_eq!(Schema::new(&testkit.snapshot()).schema_version(), 0);
+
+    testkit.create_block();
+    let snapshot = testkit.snapshot();
+    let schema = Schema::new(&snapshot);
+    assert_eq!(schema.schema_version(), SCHEMA_VERSION);
+    assert_eq!(schema.resolve_name("Alice"), Some(tx_alice.author()));
+    assert_eq!(
+        api.get_pending_transfers(TransferParty::Sender, tx_alice.author()),
+        vec![tx.hash()]
+    );
+
+    // Records written by the current version stay intact.
+    let wallet = api.get_wallet(tx_alice.author()).unwrap();
+    assert_eq!(wallet.balance, 90);
+    assert_eq!(wallet.retained_amount, 10);
+    assert_eq!(
+        api.get_pending_transfers(TransferParty::Receiver, tx_bob.author()),
+        vec![tx.hash()]
+    );
+}
+
+/// Check that the wallet balance at past heights is proved by the wallet history.
+#[test]
+fn test_balance_at_height() {
+    let (mut testkit, api) = create_testkit();
+
+    let (approver_pk, approver_sk) = api.create_approver(&mut testkit);
+    let (tx_alice, key_alice) = api.create_wallet("Alice");
+    let (tx_bob, _) = api.create_wallet("Bob");
+    testkit.create_block();
+    let created_height = testkit.height();
+
+    let tx = Transfer::sign(
+        &tx_alice.author(),
+        &tx_bob.author(),
+        &approver_pk,
+        10, // transfer amount
+        0,  // seed
+        &key_alice,
+    );
+    api.transfer(&tx);
+    testkit.create_block();
+    let transfer_height = testkit.height();
+
+    let tx = Approve::sign(&approver_pk, tx.hash(), 0, &approver_sk);
+    api.approve(&tx);
+    testkit.create_block();
+    // Blocks without changes of the wallets.
+    testkit.create_blocks_until(Height(transfer_height.0 + 4));
+
+    let alice = tx_alice.author();
+    assert_eq!(api.get_balance_at(alice, Height(created_height.0 - 1)), None);
+    assert_eq!(api.get_balance_at(alice, created_height), Some(100));
+    assert_eq!(api.get_balance_at(alice, transfer_height), Some(90));
+    assert_eq!(api.get_balance_at(alice, testkit.height()), Some(90));
+    let bob = tx_bob.author();
+    assert_eq!(api.get_balance_at(bob, transfer_height), Some(100));
+    assert_eq!(api.get_balance_at(bob, Height(transfer_height.0 + 1)), Some(110));
+
+    let result = api
+        .inner
+        .public(ApiKind::Service("cryptocurrency"))
+        .query(&BalanceAtQuery { pub_key: alice, height: Height(testkit.height().0 + 1) })
+        .get::<BalanceAtInfo>("v1/wallets/balance_at");
+    assert!(result.is_err());
+}
+
+/// Check that the wallet history is compacted into a checkpoint and the compacted
+/// entries remain provable.
+#[test]
+fn test_history_compaction() {
+    let (mut testkit, api) = create_testkit();
+
+    let (tx_alice, key_alice) = api.create_wallet("Alice");
+    testkit.create_block();
+    let created_height = testkit.height();
+
+    // Wallet creation and issues fill the history up to the threshold and beyond.
+    let issues = HISTORY_COMPACTION_THRESHOLD + 6;
+    for seed in 0..issues {
+        let tx = Issue::sign(&tx_alice.author(), 1, seed, &key_alice);
+        api.send_tx(&tx);
+    }
+    testkit.create_block();
+
+    let wallet = api.get_wallet(tx_alice.author()).unwrap();
+    assert_eq!(wallet.balance, 100 + issues);
+    assert_eq!(wallet.history_len, 8);
+    let history = api.get_wallet_history(tx_alice.author());
+    assert!(history[0].is_checkpoint());
+    assert_eq!(history[0].balance, 100 + HISTORY_COMPACTION_THRESHOLD - 1);
+    assert!(history[1..].iter().all(|entry| entry.kind == KIND_ISSUE));
+
+    let info: ArchivedHistoryInfo = api
+        .inner
+        .public(ApiKind::Service("cryptocurrency"))
+        .query(&ArchivedHistoryQuery { pub_key: tx_alice.author(), checkpoint: 0 })
+        .get("v1/wallets/archived_history")
+        .unwrap();
+    let head = info.history_head.validate(wallet.history_hash, wallet.history_len).unwrap();
+    assert_eq!(
+        head[0].1.tx_hash,
+        history::checkpoints_commitment(info.checkpoints_len, &info.checkpoints_hash)
+    );
+    let checkpoint = info.checkpoint.validate(info.checkpoints_hash, info.checkpoints_len).unwrap();
+    let checkpoint = checkpoint[0].1;
+    assert_eq!(checkpoint.entries_len, HISTORY_COMPACTION_THRESHOLD);
+    assert_eq!(checkpoint.balance, 100 + HISTORY_COMPACTION_THRESHOLD - 1);
+    let entries = info.entries.validate(checkpoint.entries_hash, checkpoint.entries_len).unwrap();
+    assert_eq!(entries[0].1.kind, KIND_CREATE_WALLET);
+    assert_eq!(info.transactions.len(), entries.len());
+
+    let result = api
+        .inner
+        .public(ApiKind::Service("cryptocurrency"))
+        .query(&ArchivedHistoryQuery { pub_key: tx_alice.author(), checkpoint: 1 })
+        .get::<ArchivedHistoryInfo>("v1/wallets/archived_history");
+    assert!(result.is_err());
+
+    // Balance at the heights compacted into the checkpoint is proved by the archived entries.
+    assert_eq!(api.get_balance_at(tx_alice.author(), Height(created_height.0 - 1)), None);
+    assert_eq!(api.get_balance_at(tx_alice.author(), created_height), Some(100));
+    assert_eq!(api.get_balance_at(tx_alice.author(), testkit.height()), Some(100 + issues));
+}
+
+/// Check that the statistics of each block are recorded and proved.
+#[test]
+fn test_block_statistics() {
+    let (mut testkit, api) = create_testkit();
+
+    let (approver_pk, approver_sk) = api.create_approver(&mut testkit);
+    let (tx_alice, key_alice) = api.create_wallet("Alice");
+    let (tx_bob, _) = api.create_wallet("Bob");
+    let (tx_carol, key_carol) = api.create_wallet("Carol");
+    testkit.create_block();
+    let from = testkit.height();
+
+    let tx = Transfer::sign(
+        &tx_alice.author(),
+        &tx_bob.author(),
+        &approver_pk,
+        10, // transfer amount
+        0,  // seed
+        &key_alice,
+    );
+    api.transfer(&tx);
+    let tx_filtered = Transfer::sign(
+        &tx_alice.author(),
+        &tx_carol.author(),
+        &approver_pk,
+        20, // transfer amount
+        1,  // seed
+        &key_alice,
+    );
+    api.transfer(&tx_filtered);
+    api.send_tx(&Issue::sign(&tx_alice.author(), 5, 0, &key_alice));
+    // Failed transactions are not counted.
+    let tx_overcharge = Transfer::sign(
+        &tx_alice.author(),
+        &tx_bob.author(),
+        &approver_pk,
+        1000, // transfer amount
+        2,    // seed
+        &key_alice,
+    );
+    api.transfer(&tx_overcharge);
+    testkit.create_block();
+
+    // Carol blocks Alice, so the second transfer is returned to Alice on the approval.
+    let tx_filter = SetSenderFilter::sign(&tx_carol.author(), FILTER_MODE_BLOCKLIST, 0, &key_carol);
+    api.send_tx(&tx_filter);
+    let tx_block = UpdateSenderList::sign(&tx_carol.author(), &tx_alice.author(), true, 0, &key_carol);
+    api.send_tx(&tx_block);
+    testkit.create_block();
+
+    api.approve(&Approve::sign(&approver_pk, tx.hash(), 0, &approver_sk));
+    api.approve(&Approve::sign(&approver_pk, tx_filtered.hash(), 1, &approver_sk));
+    testkit.create_block();
+
+    let statistics = api.get_block_statistics(from, Height(testkit.height().0 + 1));
+    assert_eq!(statistics.len(), 4);
+    assert_eq!(statistics[0].height, from.0);
+    assert_eq!(statistics[0].transactions.create_wallet, 3);
+
+    // The volume is counted when the transfers are settled, not when they are retained.
+    assert_eq!(statistics[1].transactions.transfer, 2);
+    assert_eq!(statistics[1].transactions.issue, 1);
+    assert_eq!(statistics[1].volume_transferred, 0);
+    assert_eq!(statistics[1].amount_issued, 5);
+    assert_eq!((statistics[1].pending_transfers, statistics[1].pending_value), (2, 30));
+
+    assert_eq!(statistics[2].transactions.set_sender_filter, 1);
+    assert_eq!(statistics[2].transactions.update_sender_list, 1);
+    assert_eq!(statistics[2].volume_transferred, 0);
+
+    // The filtered transfer is not counted.
+    assert_eq!(statistics[3].transactions.approve, 2);
+    assert_eq!(statistics[3].volume_transferred, 10);
+    assert_eq!((statistics[3].pending_transfers, statistics[3].pending_value), (0, 0));
+    let status = api.get_transfer_status(tx_filtered.hash()).unwrap();
+    assert_eq!(status.status, TRANSFER_STATUS_FILTERED);
+
+    let result = api
+        .inner
+        .public(ApiKind::Service("cryptocurrency"))
+        .query(&StatisticsQuery { from, to: from })
+        .get::<StatisticsInfo>("v1/statistics/blocks");
+    assert!(result.is_err());
+}
+
+/// Check that the approver inbox lists the transfers awaiting its approval.
+#[test]
+fn test_pending_approvals() {
+    let (mut testkit, api) = create_testkit();
+
+    let (approver_pk, approver_sk) = api.create_approver(&mut testkit);
+    let (tx_alice, key_alice) = api.create_wallet("Alice");
+    let (tx_bob, _) = api.create_wallet("Bob");
+    testkit.create_block();
+
+    let mut transfers = Vec::new();
+    for (seed, &amount) in [10, 20].iter().enumerate() {
+        let tx = Transfer::sign(
+            &tx_alice.author(),
+            &tx_bob.author(),
+            &approver_pk,
+            amount,
+            seed as u64,
+            &key_alice,
+        );
+        api.transfer(&tx);
+        testkit.create_block();
+        transfers.push(tx.hash());
+    }
+    testkit.create_block();
+
+    let approvals = api.get_pending_approvals(approver_pk);
+    assert_eq!(approvals.len(), 2);
+    assert_eq!(approvals[0].transfer_tx_hash, transfers[0]);
+    assert_eq!((approvals[0].amount, approvals[0].age), (10, 2));
+    assert_eq!(approvals[0].from, tx_alice.author());
+    assert_eq!(approvals[0].to, tx_bob.author());
+    assert_eq!(approvals[1].transfer_tx_hash, transfers[1]);
+    assert_eq!((approvals[1].amount, approvals[1].age), (20, 1));
+
+    let tx = Approve::sign(&approver_pk, transfers[0], 0, &approver_sk);
+    api.approve(&tx);
+    testkit.create_block();
+    api.assert_tx_status(tx.hash(), &json!({ "type": "success" }));
+
+    let approvals = api.get_pending_approvals(approver_pk);
+    assert_eq!(approvals.len(), 1);
+    assert_eq!(approvals[0].transfer_tx_hash, transfers[1]);
+    assert!(api.get_pending_approvals(tx_alice.author()).is_empty());
+}
+
+/// Check that the wallet history is served in pages and may be omitted from the wallet info.
+#[test]
+fn test_wallet_history_pages() {
+    let (mut testkit, api) = create_testkit();
+
+    let (tx_alice, key_alice) = api.create_wallet("Alice");
+    testkit.create_block();
+    for seed in 0..5 {
+        api.send_tx(&Issue::sign(&tx_alice.author(), 1, seed, &key_alice));
+    }
+    testkit.create_block();
+
+    let wallet = api.get_wallet(tx_alice.author()).unwrap();
+    assert_eq!(wallet.history_len, 6);
+
+    let info: WalletHistoryInfo = api
+        .inner
+        .public(ApiKind::Service("cryptocurrency"))
+        .query(&WalletHistoryQuery { pub_key: tx_alice.author(), offset: 2, limit: Some(3) })
+        .get("v1/wallets/history")
+        .unwrap();
+    let history = info.history.unwrap();
+    let entries = history.proof.validate(wallet.history_hash, wallet.history_len).unwrap();
+    let indices: Vec<u64> = entries.iter().map(|(index, _)| *index).collect();
+    assert_eq!(indices, vec![2, 3, 4]);
+    assert_eq!(history.transactions.len(), 3);
+
+    let info: WalletHistoryInfo = api
+        .inner
+        .public(ApiKind::Service("cryptocurrency"))
+        .query(&WalletHistoryQuery { pub_key: tx_alice.author(), offset: 6, limit: None })
+        .get("v1/wallets/history")
+        .unwrap();
+    assert!(info.history.is_none());
+
+    let info: WalletInfo = api
+        .inner
+        .public(ApiKind::Service("cryptocurrency"))
+        .query(&WalletInfoQuery { pub_key: tx_alice.author(), skip_history: true })
+        .get("v1/wallets/info")
+        .unwrap();
+    assert!(info.wallet_history.is_none());
+    let to_wallet = info.wallet_proof.to_wallet.check().unwrap();
+    assert_eq!(to_wallet.entries().count(), 1);
+}
+
+/// Check that the wallets are listed with filters and ordered by balance in the richlist.
+#[test]
+fn test_wallet_listing() {
+    let (mut testkit, api) = create_testkit();
+
+    let (tx_alice, key_alice) = api.create_wallet("Alice");
+    let (tx_bob, _) = api.create_wallet("Bob");
+    let (tx_carol, key_carol) = api.create_wallet("Carol");
+    let (tx_alina, _) = api.create_wallet("alina");
+    testkit.create_block();
+    api.send_tx(&Issue::sign(&tx_alice.author(), 50, 0, &key_alice));
+    api.send_tx(&Issue::sign(&tx_carol.author(), 20, 0, &key_carol));
+    testkit.create_block();
+
+    let query = WalletsQuery {
+        from: None,
+        limit: None,
+        name_prefix: Some("AL".to_owned()),
+        min_balance: None,
+        with_retained: false,
+        with_proof: true,
+    };
+    let page = api.get_wallets(&query);
+    let mut names: Vec<_> = page.wallets.iter().map(|wallet| wallet.name.clone()).collect();
+    names.sort();
+    assert_eq!(names, vec!["Alice", "alina"]);
+    assert!(page.next.is_none());
+    let proof = page.proof.unwrap().check().unwrap();
+    assert_eq!(proof.entries().count(), 2);
+
+    let query = WalletsQuery { name_prefix: None, min_balance: Some(120), with_proof: false, ..query };
+    let mut keys: Vec<_> = api.get_wallets(&query).wallets.iter().map(|wallet| wallet.pub_key).collect();
+    keys.sort();
+    let mut expected = vec![tx_alice.author(), tx_carol.author()];
+    expected.sort();
+    assert_eq!(keys, expected);
+
+    // Walk through all wallets page by page.
+    let mut query = WalletsQuery { min_balance: None, limit: Some(1), ..query };
+    let mut listed = Vec::new();
+    loop {
+        let page = api.get_wallets(&query);
+        assert!(page.wallets.len() <= 1);
+        listed.extend(page.wallets.into_iter().map(|wallet| wallet.pub_key));
+        match page.next {
+            Some(next) => query.from = Some(next),
+            None => break,
+        }
+    }
+    listed.sort();
+    let mut expected = vec![tx_alice.author(), tx_bob.author(), tx_carol.author(), tx_alina.author()];
+    expected.sort();
+    assert_eq!(listed, expected);
+
+    let get_richlist = |query: &RichlistQuery| -> RichlistPage {
+        api.inner
+            .public(ApiKind::Service("cryptocurrency"))
+            .query(query)
+            .get("v1/wallets/richlist")
+            .unwrap()
+    };
+    let query = RichlistQuery { from_balance: None, from: None, limit: Some(3), with_proof: true };
+    let page = get_richlist(&query);
+    let balances: Vec<_> = page.wallets.iter().map(|wallet| wallet.balance).collect();
+    assert_eq!(balances, vec![150, 120, 100]);
+    assert_eq!(page.wallets[0].pub_key, tx_alice.author());
+    let proof = page.proof.unwrap().check().unwrap();
+    assert_eq!(proof.entries().count(), 3);
+
+    // The next page starts from the cursor of the last one.
+    let next = page.next.unwrap();
+    assert_eq!(next.balance, 100);
+    let query = RichlistQuery { from_balance: Some(next.balance), from: Some(next.pub_key), ..query };
+    let page = get_richlist(&query);
+    assert_eq!(page.wallets.len(), 1);
+    assert_eq!(page.wallets[0].pub_key, next.pub_key);
+    assert!(page.next.is_none());
+
+    // The cursor is passed as a whole.
+    let result = api
+        .inner
+        .public(ApiKind::Service("cryptocurrency"))
+        .query(&RichlistQuery { from_balance: None, ..query })
+        .get::<RichlistPage>("v1/wallets/richlist");
+    assert!(result.is_err());
+}
+
+/// Check that the statuses of the transfers are tracked until they are settled or rejected.
+#[test]
+fn test_transfer_status() {
+    let (mut testkit, api) = create_testkit();
+
+    let (approver_pk, approver_sk) = api.create_approver(&mut testkit);
+    let (tx_alice, key_alice) = api.create_wallet("Alice");
+    let (tx_bob, _) = api.create_wallet("Bob");
+    testkit.create_block();
+
+    let (oracle_pk, oracle_sk) = crypto::gen_keypair();
+    let condition = crypto::hash(b"Delivery #1");
+
+    let tx_transfer = Transfer::sign(
+        &tx_alice.author(),
+        &tx_bob.author(),
+        &approver_pk,
+        10, // transfer amount
+        0,  // seed
+        &key_alice,
+    );
+    api.transfer(&tx_transfer);
+    let tx_conditional = Transfer::sign_conditional(
+        &tx_alice.author(),
+        &tx_bob.author(),
+        &oracle_pk,
+        condition,
+        true, // outcome
+        20,   // transfer amount
+        1,    // seed
+        &key_alice,
+    );
+    api.transfer(&tx_conditional);
+    testkit.create_block();
+    let transfer_height = testkit.height();
+
+    let status = api.get_transfer_status(tx_transfer.hash()).unwrap();
+    assert_eq!(status.status, TRANSFER_STATUS_PENDING);
+    assert_eq!(status.height, transfer_height.0);
+
+    let tx_approve = Approve::sign(&approver_pk, tx_transfer.hash(), 0, &approver_sk);
+    api.approve(&tx_approve);
+    let tx_attest = Attest::sign(&oracle_pk, condition, false, 0, &oracle_sk);
+    api.send_tx(&tx_attest);
+    testkit.create_block();
+
+    let status = api.get_transfer_status(tx_transfer.hash()).unwrap();
+    assert_eq!(status.status, TRANSFER_STATUS_SETTLED);
+    assert_eq!(status.resolution_tx_hash, tx_approve.hash());
+    assert_eq!(status.height, testkit.height().0);
+
+    let status = api.get_transfer_status(tx_conditional.hash()).unwrap();
+    assert_eq!(status.status, TRANSFER_STATUS_REJECTED);
+    assert_eq!(status.resolution_tx_hash, tx_attest.hash());
+
+    // Unknown transfers are proved to be absent.
+    assert!(api.get_transfer_status(tx_approve.hash()).is_none());
+}
+
+/// Check the private API for the node operators.
+#[test]
+fn test_private_api() {
+    let (mut testkit, api) = create_testkit();
+
+    let (approver_pk, _) = api.create_approver(&mut testkit);
+    let (tx_alice, key_alice) = api.create_wallet("Alice");
+    let (tx_bob, _) = api.create_wallet("Bob");
+    testkit.create_block();
+    let tx = Transfer::sign(
+        &tx_alice.author(),
+        &tx_bob.author(),
+        &approver_pk,
+        10, // transfer amount
+        0,  // seed
+        &key_alice,
+    );
+    api.transfer(&tx);
+    testkit.create_block();
+    let transfer_height = testkit.height();
+
+    let config: ServiceConfig = api
+        .inner
+        .private(ApiKind::Service("cryptocurrency"))
+        .get("v1/config")
+        .unwrap();
+    assert_eq!(config.service_name, "cryptocurrency");
+    assert_eq!(config.schema_version, SCHEMA_VERSION);
+
+    let roles: RolesInfo = api
+        .inner
+        .private(ApiKind::Service("cryptocurrency"))
+        .get("v1/roles")
+        .unwrap();
+    let validator_pk = *testkit.network().validators()[0].service_keypair().0;
+    assert_eq!(roles.validators, vec![validator_pk]);
+    assert_eq!(roles.approvers.len(), 1);
+    assert_eq!(roles.approvers[0].pub_key, approver_pk);
+    assert!(roles.oracles.is_empty());
+
+    let report = api.get_consistency_report();
+    assert!(report.consistent);
+    assert_eq!(report.total_retained, 10);
+    assert_eq!((report.pending_count, report.pending_value), (1, 10));
+
+    // Break the index of the wallets by balance and rebuild it.
+    let mut fork = testkit.blockchain_mut().fork();
+    Schema::new(&mut fork).wallets_by_balance_mut().clear();
+    testkit.blockchain_mut().merge(fork.into_patch()).unwrap();
+    let report = api.get_consistency_report();
+    assert!(!report.consistent);
+    assert_eq!(report.unindexed_wallets.len(), 3);
+
+    let response: ReindexResponse = api
+        .inner
+        .private(ApiKind::Service("cryptocurrency"))
+        .query(&ReindexQuery { seed: 0 })
+        .post("v1/reindex")
+        .unwrap();
+    testkit.create_block();
+    api.assert_tx_status(response.tx_hash, &json!({ "type": "success" }));
+    let report = api.get_consistency_report();
+    assert!(report.consistent);
+    assert_eq!((report.pending_count, report.pending_value), (1, 10));
+
+    // The pending transfer keeps the height of the block it was committed in.
+    let status = api.get_transfer_status(tx.hash()).unwrap();
+    assert_eq!(status.status, TRANSFER_STATUS_PENDING);
+    assert_eq!(status.height, transfer_height.0);
+
+    // Only validators may reindex.
+    let tx = Reindex::sign(&tx_alice.author(), 0, &key_alice);
+    api.send_tx(&tx);
+    testkit.create_block();
+    api.assert_tx_status(
+        tx.hash(),
+        &json!({ "type": "error", "code": 27, "description": "Transaction author is not a validator" }),
+    );
+}
+
+/// Check that the subscribers' event logs follow the wallet history and the transfers.
+#[test]
+fn test_wallet_events() {
+    let (mut testkit, api) = create_testkit();
+
+    let (approver_pk, approver_sk) = api.create_approver(&mut testkit);
+    let (tx_alice, key_alice) = api.create_wallet("Alice");
+    let (tx_bob, _) = api.create_wallet("Bob");
+    testkit.create_block();
+
+    let events = api.get_events(tx_bob.author(), 0);
+    assert_eq!(events.events.len(), 1);
+    assert_eq!(events.events[0].kind, EVENT_HISTORY_ENTRY);
+    assert_eq!(events.events[0].tx_hash, tx_bob.hash());
+    let bob_cursor = events.next;
+    let approver_cursor = api.get_events(approver_pk, 0).next;
+
+    let tx = Transfer::sign(
+        &tx_alice.author(),
+        &tx_bob.author(),
+        &approver_pk,
+        10, // transfer amount
+        0,  // seed
+        &key_alice,
+    );
+    api.transfer(&tx);
+    testkit.create_block();
+    let transfer_tx_hash = tx.hash();
+
+    let events = api.get_events(approver_pk, approver_cursor).events;
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].kind, EVENT_TRANSFER_AWAITING_APPROVAL);
+    assert_eq!(events[0].transfer_tx_hash, transfer_tx_hash);
+    assert_eq!(events[0].height, testkit.height().0);
+    assert!(api.get_events(tx_bob.author(), bob_cursor).events.is_empty());
+
+    let tx = Approve::sign(&approver_pk, transfer_tx_hash, 0, &approver_sk);
+    api.approve(&tx);
+    testkit.create_block();
+
+    let kinds: Vec<_> = api
+        .get_events(tx_bob.author(), bob_cursor)
+        .events
+        .iter()
+        .map(|event| (event.kind, event.tx_hash))
+        .collect();
+    assert_eq!(kinds, vec![(EVENT_HISTORY_ENTRY, tx.hash()), (EVENT_TRANSFER_SETTLED, tx.hash())]);
+}
+
+/// Check that the unsigned messages match the ones signed by the client library
+/// and can be submitted along with the detached signature.
+#[test]
+fn test_unsigned_transactions() {
+    let (mut testkit, api) = create_testkit();
+
+    let (pubkey, key) = crypto::gen_keypair();
+    let expected = messages::to_hex_string(&CreateWallet::sign("Alice", &pubkey, &key));
+    let unsigned = api.get_unsigned_transaction(
+        pubkey,
+        WalletTransactions::CreateWallet(CreateWallet {
+            name: "Alice".to_owned(),
+        }),
+    );
+    let (message, signature) = expected.split_at(expected.len() - 128);
+    assert_eq!(unsigned.message, message);
+
+    let signature = serde_json::from_value(json!(signature)).unwrap();
+    let submitted: SubmittedTransaction = api
+        .inner
+        .public(ApiKind::Service("cryptocurrency"))
+        .query(&SignedTransactionQuery {
+            message: unsigned.message.clone(),
+            signature,
+        })
+        .post("v1/transactions/signed")
+        .unwrap();
+    testkit.create_block();
+    api.assert_tx_status(submitted.tx_hash, &json!({ "type": "success" }));
+    assert_eq!(api.get_wallet(pubkey).unwrap().name, "Alice");
+
+    // A signature made with another key is rejected.
+    let (_, other_key) = crypto::gen_keypair();
+    let other = messages::to_hex_string(&CreateWallet::sign("Alice", &pubkey, &other_key));
+    let signature = serde_json::from_value(json!(&other[other.len() - 128..])).unwrap();
+    let response = api
+        .inner
+        .public(ApiKind::Service("cryptocurrency"))
+        .query(&SignedTransactionQuery {
+            message: unsigned.message,
+            signature,
+        })
+        .post::<SubmittedTransaction>("v1/transactions/signed");
+    assert!(response.is_err());
+}
+
+/// Check that the unsigned messages of every transaction type match the ones signed
+/// by the client library.
+#[test]
+fn test_unsigned_messages_of_all_transactions() {
+    let (_testkit, api) = create_testkit();
+
+    let (pk, sk) = crypto::gen_keypair();
+    let (other, other_sk) = crypto::gen_keypair();
+    let hash = crypto::hash(b"Transaction");
+    let transfer = Transfer {
+        from: pk,
+        to: other,
+        approver: other,
+        amount: 10,
+        seed: 1,
+        condition: Condition::none(),
+        outcome: false,
+    };
+    let conditional = Transfer { condition: Condition(hash), outcome: true, ..transfer };
+    let approval = transfer.sign_approval(&other_sk);
+
+    let cases = vec![
+        (
+            WalletTransactions::Transfer(transfer),
+            Transfer::sign(&pk, &other, &other, 10, 1, &sk),
+        ),
+        (
+            WalletTransactions::Transfer(conditional),
+            Transfer::sign_conditional(&pk, &other, &other, hash, true, 10, 1, &sk),
+        ),
+        (
+            WalletTransactions::Approve(Approve { approver: pk, transfer_tx_hash: hash, seed: 1 }),
+            Approve::sign(&pk, hash, 1, &sk),
+        ),
+        (
+            WalletTransactions::Issue(Issue { amount: 10, seed: 1 }),
+            Issue::sign(&pk, 10, 1, &sk),
+        ),
+        (
+            WalletTransactions::CreateWallet(CreateWallet { name: "Alice".to_owned() }),
+            CreateWallet::sign("Alice", &pk, &sk),
+        ),
+        (
+            WalletTransactions::SetSenderFilter(SetSenderFilter { mode: FILTER_MODE_BLOCKLIST, seed: 1 }),
+            SetSenderFilter::sign(&pk, FILTER_MODE_BLOCKLIST, 1, &sk),
+        ),
+        (
+            WalletTransactions::UpdateSenderList(UpdateSenderList { sender: other, listed: true, seed: 1 }),
+            UpdateSenderList::sign(&pk, &other, true, 1, &sk),
+        ),
+        (
+            WalletTransactions::CreateInvoice(CreateInvoice {
+                amount: 10,
+                due_height: 5,
+                approver: other,
+                description_hash: hash,
+                seed: 1,
+            }),
+            CreateInvoice::sign(&pk, 10, 5, &other, hash, 1, &sk),
+        ),
+        (
+            WalletTransactions::PayInvoice(PayInvoice { invoice_id: hash, seed: 1 }),
+            PayInvoice::sign(&pk, hash, 1, &sk),
+        ),
+        (
+            WalletTransactions::Refund(Refund { transfer_tx_hash: hash, amount: 10, seed: 1 }),
+            Refund::sign(&pk, hash, 10, 1, &sk),
+        ),
+        (
+            WalletTransactions::Attest(Attest { condition: hash, outcome: true, seed: 1 }),
+            Attest::sign(&pk, hash, true, 1, &sk),
+        ),
+        (
+            WalletTransactions::TransferWithApproval(TransferWithApproval {
+                from: pk,
+                to: other,
+                approver: other,
+                amount: 10,
+                seed: 1,
+                approver_signature: approval.as_ref().to_vec(),
+            }),
+            TransferWithApproval::sign(&pk, &other, &other, 10, 1, &approval, &sk),
+        ),
+        (
+            WalletTransactions::RegisterApprover(RegisterApprover { stake: 10, seed: 1 }),
+            RegisterApprover::sign(&pk, 10, 1, &sk),
+        ),
+        (
+            WalletTransactions::SlashApprover(SlashApprover { approver: other, amount: 10, seed: 1 }),
+            SlashApprover::sign(&pk, &other, 10, 1, &sk),
+        ),
+        (
+            WalletTransactions::Reindex(Reindex { seed: 1 }),
+            Reindex::sign(&pk, 1, &sk),
+        ),
+        (
+            WalletTransactions::UnregisterApprover(UnregisterApprover { seed: 1 }),
+            UnregisterApprover::sign(&pk, 1, &sk),
+        ),
+    ];
+
+    // The exhaustive match breaks the build when a transaction is added without being
+    // listed above, and every arm must be covered by a case.
+    let mut covered = BTreeSet::new();
+    for (transaction, _) in &cases {
+        let name = match transaction {
+            WalletTransactions::Transfer(_) => "Transfer",
+            WalletTransactions::Approve(_) => "Approve",
+            WalletTransactions::Issue(_) => "Issue",
+            WalletTransactions::CreateWallet(_) => "CreateWallet",
+            WalletTransactions::SetSenderFilter(_) => "SetSenderFilter",
+            WalletTransactions::UpdateSenderList(_) => "UpdateSenderList",
+            WalletTransactions::CreateInvoice(_) => "CreateInvoice",
+            WalletTransactions::PayInvoice(_) => "PayInvoice",
+            WalletTransactions::Refund(_) => "Refund",
+            WalletTransactions::Attest(_) => "Attest",
+            WalletTransactions::TransferWithApproval(_) => "TransferWithApproval",
+            WalletTransactions::RegisterApprover(_) => "RegisterApprover",
+            WalletTransactions::SlashApprover(_) => "SlashApprover",
+            WalletTransactions::Reindex(_) => "Reindex",
+            WalletTransactions::UnregisterApprover(_) => "UnregisterApprover",
+        };
+        covered.insert(name);
+    }
+    assert_eq!(covered.len(), 15);
+
+    for (transaction, signed) in cases {
+        let expected = messages::to_hex_string(&signed);
+        let unsigned = api.get_unsigned_transaction(pk, transaction.clone());
+        assert_eq!(unsigned.message, &expected[..expected.len() - 128], "{:?}", transaction);
+    }
+}
+
+/// Check that the simulated transactions report their result without being committed.
+#[test]
+fn test_transaction_simulation() {
+    let (mut testkit, api) = create_testkit();
+
+    let (approver_pk, _) = api.create_approver(&mut testkit);
+    let (tx_alice, key_alice) = api.create_wallet("Alice");
+    let (tx_bob, _) = api.create_wallet("Bob");
+    testkit.create_block();
+    let alice = tx_alice.author();
+
+    let transfer = |to: PublicKey, amount: u64| Transfer {
+        from: alice,
+        to,
+        approver: approver_pk,
+        amount,
+        seed: 0,
+        condition: Condition::none(),
+        outcome: false,
+    };
+
+    let result = api.simulate(SimulationQuery::Unsigned(UnsignedTransactionQuery {
+        author: alice,
+        transaction: WalletTransactions::Transfer(transfer(tx_bob.author(), 10)),
+    }));
+    assert!(result.error.is_none());
+    assert_eq!(
+        result.wallets,
+        vec![WalletDelta {
+            pub_key: alice,
+            balance: -10,
+            retained_amount: 10,
+        }]
+    );
+
+    let result = api.simulate(SimulationQuery::Unsigned(UnsignedTransactionQuery {
+        author: alice,
+        transaction: WalletTransactions::Transfer(transfer(tx_bob.author(), 1000)),
+    }));
+    let error = result.error.unwrap();
+    assert_eq!(error.name, "InsufficientCurrencyAmount");
+    assert!(result.wallets.is_empty());
+
+    let (unknown, _) = crypto::gen_keypair();
+    let tx = Transfer::sign(&alice, &unknown, &approver_pk, 10, 0, &key_alice);
+    let result = api.simulate(SimulationQuery::Signed {
+        tx_body: messages::to_hex_string(&tx),
+    });
+    assert_eq!(result.tx_hash, tx.hash());
+    assert_eq!(result.error.unwrap().name, "ReceiverNotFound");
+
+    // Nothing is committed.
+    testkit.create_block();
+    let wallet = api.get_wallet(alice).unwrap();
+    assert_eq!((wallet.balance, wallet.retained_amount), (100, 0));
+    assert!(api.get_transfer_status(tx.hash()).is_none());
+
+    // Records written by a newer version fail the simulation instead of the API worker.
+    let mut fork = testkit.blockchain_mut().fork();
+    Schema::new(&mut fork).schema_version_mut().set(SCHEMA_VERSION + 1);
+    testkit.blockchain_mut().merge(fork.into_patch()).unwrap();
+    let result = api
+        .inner
+        .public(ApiKind::Service("cryptocurrency"))
+        .query(&SimulationQuery::Signed {
+            tx_body: messages::to_hex_string(&tx),
+        })
+        .post::<SimulationResult>("v1/transactions/simulate");
+    assert!(result.is_err());
+}
+
+/// Check the verification of the wallet information from the precommits down to the history.
+#[test]
+fn test_verify_wallet_info() {
+    let (mut testkit, api) = create_testkit();
+
+    let (tx_alice, _) = api.create_wallet("Alice");
+    let (tx_bob, _) = api.create_wallet("Bob");
+    testkit.create_block();
+    let alice = tx_alice.author();
+
+    let validators: Vec<_> = testkit
+        .network()
+        .validators()
+        .iter()
+        .map(|validator| validator.public_keys().consensus_key)
+        .collect();
+    let mut info: WalletInfo = api
+        .inner
+        .public(ApiKind::Service("cryptocurrency"))
+        .query(&WalletQuery { pub_key: alice })
+        .get("v1/wallets/info")
+        .unwrap();
+
+    let verified = verify_wallet_info(&info, &alice, &validators).unwrap();
+    assert_eq!(verified.height, testkit.height());
+    assert_eq!(verified.wallet.name, "Alice");
+    let history = verified.history.unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].tx_hash, tx_alice.hash());
+
+    let (stranger, _) = crypto::gen_keypair();
+    assert_matches!(
+        verify_wallet_info(&info, &alice, &[stranger]),
+        Err(verify::Error::UnknownValidator { validator: 0 })
+    );
+    assert_matches!(
+        verify_wallet_info(&info, &tx_bob.author(), &validators),
+        Err(verify::Error::WalletNotProved)
+    );
+
+    info.wallet_history.as_mut().unwrap().transactions.clear();
+    assert_matches!(
+        verify_wallet_info(&info, &alice, &validators),
+        Err(verify::Error::TransactionCountMismatch { expected: 1, received: 0 })
+    );
+}
+
+/// Check that the batch query proves all requested wallets with a single proof.
+#[test]
+fn test_wallets_batch() {
+    let (mut testkit, api) = create_testkit();
+
+    let (tx_alice, _) = api.create_wallet("Alice");
+    let (tx_bob, _) = api.create_wallet("Bob");
+    testkit.create_block();
+    let (unknown, _) = crypto::gen_keypair();
+
+    let pub_keys = vec![tx_alice.author(), unknown, tx_bob.author(), tx_alice.author()];
+    let info: WalletsBatchInfo = api
+        .inner
+        .public(ApiKind::Service("cryptocurrency"))
+        .query(&WalletsBatchQuery { pub_keys })
+        .post("v1/wallets/batch")
+        .unwrap();
+
+    let to_table = info.to_table.check().unwrap();
+    assert_eq!(to_table.merkle_root(), *info.block_proof.block.state_hash());
+    let (_, &wallets_hash) = to_table.entries().next().unwrap();
+
+    let to_wallets = info.to_wallets.check().unwrap();
+    assert_eq!(to_wallets.merkle_root(), wallets_hash);
+    let names: BTreeMap<_, _> = to_wallets
+        .entries()
+        .map(|(&key, wallet)| (key, wallet.name.clone()))
+        .collect();
+    assert_eq!(names.len(), 2);
+    assert_eq!(names[&tx_alice.author()], "Alice");
+    assert_eq!(names[&tx_bob.author()], "Bob");
+    assert_eq!(to_wallets.missing_keys().collect::<Vec<_>>(), vec![&unknown]);
+
+    let pub_keys = (0..=wallet::MAX_WALLETS_BATCH_LEN)
+        .map(|_| crypto::gen_keypair().0)
+        .collect();
+    let response = api
+        .inner
+        .public(ApiKind::Service("cryptocurrency"))
+        .query(&WalletsBatchQuery { pub_keys })
+        .post::<WalletsBatchInfo>("v1/wallets/batch");
+    assert!(response.is_err());
+}
+
+/// Check that the oracle attestation notifies the parties of the transfers it settles
+/// and that the keys without a wallet get no events.
+#[test]
+fn test_wallet_events_attestation() {
+    let (mut testkit, api) = create_testkit();
+
+    let (tx_alice, key_alice) = api.create_wallet("Alice");
+    let (tx_bob, _) = api.create_wallet("Bob");
+    testkit.create_block();
+    let bob_cursor = api.get_events(tx_bob.author(), 0).next;
+
+    let (oracle_pk, oracle_sk) = crypto::gen_keypair();
+    let condition = crypto::hash(b"Delivery #1");
+    let tx_transfer = Transfer::sign_conditional(
+        &tx_alice.author(),
+        &tx_bob.author(),
+        &oracle_pk,
+        condition,
+        true, // outcome
+        10,   // transfer amount
+        0,    // seed
+        &key_alice,
+    );
+    api.transfer(&tx_transfer);
+    testkit.create_block();
+    let alice_cursor = api.get_events(tx_alice.author(), 0).next;
+    assert!(api.get_events(tx_bob.author(), bob_cursor).events.is_empty());
+
+    let tx = Attest::sign(&oracle_pk, condition, true, 0, &oracle_sk);
+    api.send_tx(&tx);
+    testkit.create_block();
+    api.assert_tx_status(tx.hash(), &json!({ "type": "success" }));
+
+    let expected = vec![
+        (EVENT_HISTORY_ENTRY, Hash::zero()),
+        (EVENT_TRANSFER_SETTLED, tx_transfer.hash()),
+    ];
+    for &(key, cursor) in &[(tx_alice.author(), alice_cursor), (tx_bob.author(), bob_cursor)] {
+        let events = api.get_events(key, cursor).events;
+        assert!(events.iter().all(|event| event.tx_hash == tx.hash()));
+        let kinds: Vec<_> = events.iter().map(|event| (event.kind, event.transfer_tx_hash)).collect();
+        assert_eq!(kinds, expected);
+    }
+
+    let page = api.get_events(oracle_pk, 0);
+    assert!(page.events.is_empty());
+    assert_eq!((page.first, page.pruned, page.next), (0, 0, 0));
+}
+
+/// Check that the event log keeps the latest events only, including the events of
+/// the history entries compacted in the same block.
+#[test]
+fn test_wallet_events_pruned() {
+    let (mut testkit, api) = create_testkit();
+
+    let (tx_alice, key_alice) = api.create_wallet("Alice");
+    testkit.create_block();
+
+    let issues = MAX_EVENTS_LOG_LEN + 10;
+    for seed in 0..issues {
+        let tx = Issue::sign(&tx_alice.author(), 1, seed, &key_alice);
+        api.send_tx(&tx);
+    }
+    testkit.create_block();
+    assert!(api.get_wallet_history(tx_alice.author())[0].is_checkpoint());
+
+    // The event of the wallet creation and the oldest issues are pruned.
+    let page = api.get_events(tx_alice.author(), 0);
+    assert_eq!(page.first, issues + 1 - MAX_EVENTS_LOG_LEN);
+    assert_eq!(page.pruned, page.first);
+    assert_eq!(page.events.len() as u64, MAX_EVENTS_PAGE_LEN);
+    assert_eq!(page.next, page.first + MAX_EVENTS_PAGE_LEN);
+    assert!(page
+        .events
+        .iter()
+        .all(|event| event.kind == EVENT_HISTORY_ENTRY && event.height == testkit.height().0));
+
+    let mut cursor = page.next;
+    let mut count = page.events.len() as u64;
+    loop {
+        let page = api.get_events(tx_alice.author(), cursor);
+        assert_eq!(page.pruned, 0);
+        if page.events.is_empty() {
+            break;
+        }
+        count += page.events.len() as u64;
+        cursor = page.next;
+    }
+    assert_eq!(count, MAX_EVENTS_LOG_LEN);
+    assert_eq!(cursor, issues + 1);
+}
+
+/// Check that the records of the baseline layout are converted before the transactions
+/// of the first block after the upgrade read them.
+#[test]
+fn test_schema_migration_from_baseline() {
+    use exonum::proto::ProtobufConvert;
+    use protobuf::Message;
+
+    let (mut testkit, api) = create_testkit();
+
+    let (approver_pk, approver_sk) = api.create_approver(&mut testkit);
+    let (tx_alice, key_alice) = api.create_wallet("Alice");
+    let (tx_bob, _) = api.create_wallet("Bob");
+    testkit.create_block();
+    let (alice, bob) = (tx_alice.author(), tx_bob.author());
+
+    api.send_tx(&Issue::sign(&alice, 50, 0, &key_alice));
+    let tx_settled = Transfer::sign(&alice, &bob, &approver_pk, 20, 0, &key_alice);
+    api.transfer(&tx_settled);
+    testkit.create_block();
+    api.approve(&Approve::sign(&approver_pk, tx_settled.hash(), 0, &approver_sk));
+    let tx_pending = Transfer::sign(&alice, &bob, &approver_pk, 30, 1, &key_alice);
+    api.transfer(&tx_pending);
+    testkit.create_block();
+    api.assert_tx_status(tx_pending.hash(), &json!({ "type": "success" }));
+
+    let fields = |entry: &HistoryEntry| {
+        (
+            entry.tx_hash,
+            entry.kind,
+            entry.counterparty,
+            entry.balance_delta,
+            entry.retained_delta,
+            entry.balance,
+            entry.height,
+        )
+    };
+    let histories: Vec<(PublicKey, Vec<HistoryEntry>)> = vec![
+        (alice, api.get_wallet_history(alice)),
+        (bob, api.get_wallet_history(bob)),
+    ];
+
+    // Rewrite the records of Alice and Bob in the baseline layout: the histories are
+    // the lists of the transaction hashes, the pending transfer is encoded without
+    // the condition, and there is no version, name registry or party indexes.
+    let mut fork = testkit.blockchain_mut().fork();
+    for (key, history) in &histories {
+        let (legacy_len, legacy_hash) = {
+            let mut legacy: ProofListIndex<_, Hash> =
+                ProofListIndex::new_in_family("cryptocurrency.wallet_history", key, &mut fork);
+            legacy.extend(history.iter().map(|entry| entry.tx_hash));
+            (legacy.len(), legacy.merkle_root())
+        };
+        let mut schema = Schema::new(&mut fork);
+        schema.wallet_history_mut(key).clear();
+        let stored = schema.wallet(key).unwrap();
+        schema.names_mut().remove(&wallet::name_key(&stored.name));
+        let stored = Wallet::new(
+            *key,
+            &stored.name,
+            stored.balance,
+            stored.retained_amount,
+            legacy_len,
+            legacy_hash,
+        );
+        schema.wallets_mut().put(key, stored);
+    }
+    {
+        let mut schema = Schema::new(&mut fork);
+        schema.schema_version_mut().remove();
+        let parties = [
+            (TransferParty::Sender, alice),
+            (TransferParty::Receiver, bob),
+            (TransferParty::Approver, approver_pk),
+        ];
+        for &(party, key) in &parties {
+            schema.pending_transfers_mut(party, &key).clear();
+            schema.pending_transfers_roots_mut(party).remove(&key);
+        }
+    }
+    {
+        let mut pb = proto::Transfer::new();
+        pb.set_from(alice.to_pb());
+        pb.set_to(bob.to_pb());
+        pb.set_approver(approver_pk.to_pb());
+        pb.set_amount(30);
+        pb.set_seed(1);
+        let mut transfers: ProofMapIndex<_, Hash, Vec<u8>> =
+            ProofMapIndex::new("cryptocurrency.transfers", &mut fork);
+        transfers.put(&tx_pending.hash(), pb.write_to_bytes().unwrap());
+    }
+    testkit.blockchain_mut().merge(fork.into_patch()).unwrap();
+
+    // The first block after the upgrade reads the names and the pending transfers.
+    let (pk, sk) = crypto::gen_keypair();
+    let tx_create = CreateWallet::sign("alice ", &pk, &sk);
+    api.send_tx(&tx_create);
+    let tx_approve = Approve::sign(&approver_pk, tx_pending.hash(), 1, &approver_sk);
+    api.approve(&tx_approve);
+    testkit.create_block();
+    api.assert_tx_status(
+        tx_create.hash(),
+        &json!({ "type": "error", "code": 13, "description": "Wallet name is already taken" }),
+    );
+    api.assert_tx_status(tx_approve.hash(), &json!({ "type": "success" }));
+
+    let snapshot = testkit.snapshot();
+    let schema = Schema::new(&snapshot);
+    assert_eq!(schema.schema_version(), SCHEMA_VERSION);
+    assert_eq!(schema.resolve_name("Alice"), Some(alice));
+    assert!(api.get_pending_transfers(TransferParty::Sender, alice).is_empty());
+
+    let wallet = api.get_wallet(alice).unwrap();
+    assert_eq!(wallet.balance, 100);
+    assert_eq!(wallet.retained_amount, 0);
+    let wallet = api.get_wallet(bob).unwrap();
+    assert_eq!(wallet.balance, 150);
+
+    // The converted histories match the ones recorded by the current layout,
+    // followed by the entries of the approval.
+    for (key, history) in &histories {
+        let legacy: ProofListIndex<_, Hash> =
+            ProofListIndex::new_in_family("cryptocurrency.wallet_history", key, &snapshot);
+        assert!(legacy.is_empty());
+
+        let converted = api.get_wallet_history(*key);
+        assert_eq!(converted.len(), history.len() + 1);
+        for (converted, original) in converted.iter().zip(history) {
+            assert_eq!(fields(converted), fields(original));
+        }
+        assert_eq!(converted[history.len()].tx_hash, tx_approve.hash());
+        assert_eq!(converted[history.len()].kind, KIND_APPROVE);
+    }
+}
+
+/// Check that the pending transfers of the baseline layout get the statuses with the height
+/// of the block they were committed in, and are counted in the pending totals once.
+#[test]
+fn test_schema_migration_pending_status_height() {
+    let (mut testkit, api) = create_testkit();
+
+    let (approver_pk, _) = api.create_approver(&mut testkit);
+    let (tx_alice, key_alice) = api.create_wallet("Alice");
+    let (tx_bob, _) = api.create_wallet("Bob");
+    testkit.create_block();
+    let (alice, bob) = (tx_alice.author(), tx_bob.author());
+
+    let tx = Transfer::sign(&alice, &bob, &approver_pk, 10, 0, &key_alice);
+    api.transfer(&tx);
+    testkit.create_block();
+    let transfer_height = testkit.height();
+    testkit.create_block();
+
+    // The baseline has no version, transfer statuses or party indexes.
+    let mut fork = testkit.blockchain_mut().fork();
+    {
+        let mut schema = Schema::new(&mut fork);
+        schema.schema_version_mut().remove();
+        schema.transfer_statuses_mut().remove(&tx.hash());
+        let parties = [
+            (TransferParty::Sender, alice),
+            (TransferParty::Receiver, bob),
+            (TransferParty::Approver, approver_pk),
+        ];
+        for &(party, key) in &parties {
+            schema.pending_transfers_mut(party, &key).clear();
+            schema.pending_transfers_roots_mut(party).remove(&key);
+        }
+    }
+    testkit.blockchain_mut().merge(fork.into_patch()).unwrap();
+
+    testkit.create_block();
+    assert_eq!(Schema::new(&testkit.snapshot()).schema_version(), SCHEMA_VERSION);
+
+    let status = api.get_transfer_status(tx.hash()).unwrap();
+    assert_eq!(status.status, TRANSFER_STATUS_PENDING);
+    assert_eq!(status.height, transfer_height.0);
+    assert_ne!(status.height, testkit.height().0);
+
+    let report = api.get_consistency_report();
+    assert!(report.consistent);
+    assert_eq!((report.pending_count, report.pending_value), (1, 10));
+    assert_eq!(api.get_pending_transfers(TransferParty::Sender, alice), vec![tx.hash()]);
+}
+
+#[test]
+fn test_unknown_wallet_request() {
+    let (_testkit, api) = create_testkit();
+
+    // Transaction is sent by API, but isn't committed.
+    let (tx, _) = api.create_wallet("Alice");
+
+    api.assert_no_wallet(tx.author());
+}
+
+/// Wrapper for the cryptocurrency service API allowing to easily use it
+/// (compared to `TestKitApi` calls).
+struct CryptocurrencyApi {
+    pub inner: TestKitApi,
+}
+
+impl CryptocurrencyApi {
+    /// Generates a wallet creation transaction with a random key pair, sends it over HTTP,
+    /// and checks the synchronous result (i.e., the hash of the transaction returned
+    /// within the response).
+    /// Note that the transaction is not immediately added to the blockchain, but rather is put
+    /// to the pool of unconfirmed transactions.
+    fn create_wallet(&self, name: &str) -> (Signed<RawTransaction>, SecretKey) {
+        let (pubkey, key) = crypto::gen_keypair();
+        // Create a pre-signed transaction
+        let tx = CreateWallet::sign(name, &pubkey, &key);
+
+        let data = messages::to_hex_string(&tx);
+        let tx_info: TransactionResponse = self
+            .inner
+            .public(ApiKind::Explorer)
+            .query(&json!({ "tx_body": data }))
+            .post("v1/transactions")
+            .unwrap();
+        assert_eq!(tx_info.tx_hash, tx.hash());
+        (tx, key)
+    }
+
+    fn get_wallet(&self, pub_key: PublicKey) -> Option<Wallet> {
+        let wallet_info = self
+            .inner
+            .public(ApiKind::Service("cryptocurrency"))
+            .query(&WalletQuery { pub_key })
+            .get::<WalletInfo>("v1/wallets/info")
+            .unwrap();
+
+        let to_wallet = wallet_info.wallet_proof.to_wallet.check().unwrap();
+        let (_, wallet) = to_wallet.all_entries().find(|(&key, _)| key == pub_key)?;
+        wallet.cloned()
+    }
+
+    fn get_wallet_history(&self, pub_key: PublicKey) -> Vec<HistoryEntry> {
+        let wallet = self.get_wallet(pub_key).unwrap();
+        let wallet_info = self
+            .inner
+            .public(ApiKind::Service("cryptocurrency"))
+            .query(&WalletQuery { pub_key })
+            .get::<WalletInfo>("v1/wallets/info")
+            .unwrap();
+
+        let history = wallet_info.wallet_history.unwrap();
+        let entries = history.proof.validate(wallet.history_hash, wallet.history_len).unwrap();
+        entries.into_iter().map(|(_, entry)| entry.clone()).collect()
+    }
+
+    /// Returns the balance of the wallet at the given height, checking that it agrees
+    /// with the proved history entries.
+    fn get_balance_at(&self, pub_key: PublicKey, height: Height) -> Option<u64> {
+        let info = self
+            .inner
+            .public(ApiKind::Service("cryptocurrency"))
+            .query(&BalanceAtQuery { pub_key, height })
+            .get::<BalanceAtInfo>("v1/wallets/balance_at")
+            .unwrap();
+        assert_eq!(info.height_block_proof.block.height(), height);
+
+        let to_wallet = info.wallet_proof.to_wallet.check().unwrap();
+        let (_, wallet) = to_wallet.all_entries().find(|(&key, _)| key == pub_key).unwrap();
+        let wallet = wallet.unwrap();
+        let entries = info.entries.unwrap().validate(wallet.history_hash, wallet.history_len).unwrap();
+        if let Some(archived) = info.archived {
+            let balance = Self::check_archived_balance(&entries[0].1, &archived, height);
+            assert_eq!(balance, info.balance);
+            return balance;
+        }
+
+        let last = entries.iter().rev().find(|(_, entry)| entry.height <= height.0);
+        if let Some((index, _)) = last {
+            if let Some((_, next)) = entries.iter().find(|(next, _)| *next == index + 1) {
+                assert!(next.height > height.0);
+            } else {
+                assert_eq!(index + 1, wallet.history_len);
+            }
+        } else {
+            assert_eq!(entries[0].0, 0);
+        }
+        let balance = last.map(|(_, entry)| entry.balance);
+        assert_eq!(balance, info.balance);
+        balance
+    }
+
+    fn get_block_statistics(&self, from: Height, to: Height) -> Vec<BlockStatistics> {
+        let info = self
+            .inner
+            .public(ApiKind::Service("cryptocurrency"))
+            .query(&StatisticsQuery { from, to })
+            .get::<StatisticsInfo>("v1/statistics/blocks")
+            .unwrap();
+
+        let to_table = info.to_table.check().unwrap();
+        let (_, &statistics_hash) = to_table.entries().next().unwrap();
+        let statistics = info.statistics.unwrap().validate(statistics_hash, info.statistics_len).unwrap();
+        statistics.into_iter().map(|(_, statistics)| statistics.clone()).collect()
+    }
+
+    /// Returns the transfers awaiting the approval, checking the proof of each of them.
+    fn get_pending_approvals(&self, approver: PublicKey) -> Vec<PendingApproval> {
+        let info = self
+            .inner
+            .public(ApiKind::Service("cryptocurrency"))
+            .query(&PendingApprovalsQuery { approver })
+            .get::<PendingApprovalsInfo>("v1/approvals/pending")
+            .unwrap();
+
+        info.to_table.check().unwrap();
+        for approval in &info.approvals {
+            let proof = approval.proof.check().unwrap();
+            let (_, transfer) = proof.entries().next().unwrap();
+            assert_eq!(transfer.approver, approver);
+            assert_eq!(transfer.amount, approval.amount);
+        }
+        info.approvals
+    }
+
+    fn get_wallets(&self, query: &WalletsQuery) -> WalletsPage {
+        self.inner
+            .public(ApiKind::Service("cryptocurrency"))
+            .query(query)
+            .get("v1/wallets")
+            .unwrap()
+    }
+
+    fn get_transfer_status(&self, hash: Hash) -> Option<TransferStatus> {
+        let info = self
+            .inner
+            .public(ApiKind::Service("cryptocurrency"))
+            .query(&TransferQuery { hash })
+            .get::<TransferStatusInfo>("v1/transfers/status")
+            .unwrap();
+
+        let to_status = info.status_proof.to_status.check().unwrap();
+        let (_, status) = to_status.all_entries().find(|(&key, _)| key == hash)?;
+        status.cloned()
+    }
+
+    fn get_unsigned_transaction(
+        &self,
+        author: PublicKey,
+        transaction: WalletTransactions,
+    ) -> UnsignedTransaction {
+        self.inner
+            .public(ApiKind::Service("cryptocurrency"))
+            .query(&UnsignedTransactionQuery { author, transaction })
+            .post("v1/transactions/unsigned")
+            .unwrap()
+    }
+
+    fn simulate(&self, query: SimulationQuery) -> SimulationResult {
+        self.inner
+            .public(ApiKind::Service("cryptocurrency"))
+            .query(&query)
+            .post("v1/transactions/simulate")
+            .unwrap()
+    }
+
+    fn get_events(&self, pub_key: PublicKey, from: u64) -> EventsPage {
+        self.inner
+            .public(ApiKind::Service("cryptocurrency"))
+            .query(&EventsQuery { pub_key, from, limit: None })
+            .get("v1/events")
+            .unwrap()
+    }
+
+    fn get_consistency_report(&self) -> ConsistencyReport {
+        self.inner
+            .private(ApiKind::Service("cryptocurrency"))
+            .get("v1/consistency")
+            .unwrap()
+    }
+
+    /// Returns the balance at the given height proved by the compacted entries, checking
+    /// that they are committed to by the checkpoint entry of the wallet history.
+    fn check_archived_balance(
+        head: &HistoryEntry,
+        archived: &ArchivedBalanceProof,
+        height: Height,
+    ) -> Option<u64> {
+        assert!(head.is_checkpoint());
+        assert!(head.height > height.0);
+        assert_eq!(
+            head.tx_hash,
+            history::checkpoints_commitment(archived.checkpoints_len, &archived.checkpoints_hash)
+        );
+        let checkpoints = archived
+            .checkpoints
+            .validate(archived.checkpoints_hash, archived.checkpoints_len)
+            .unwrap();
+        let (_, checkpoint) = checkpoints
+            .iter()
+            .find(|(index, _)| *index == archived.checkpoint)
+            .unwrap();
+        assert!(checkpoint.last_height > height.0);
+        let previous = checkpoints
+            .iter()
+            .find(|(index, _)| *index + 1 == archived.checkpoint)
+            .map(|(_, previous)| previous);
+        if archived.checkpoint > 0 {
+            assert!(previous.unwrap().last_height <= height.0);
+        }
+
+        let entries = archived
+            .entries
+            .validate(checkpoint.entries_hash, checkpoint.entries_len)
+            .unwrap();
+        let last = entries.iter().rev().find(|(_, entry)| entry.height <= height.0);
+        match last {
+            Some((index, entry)) => {
+                let (_, next) = entries.iter().find(|(next, _)| *next == index + 1).unwrap();
+                assert!(next.height > height.0);
+                Some(entry.balance)
+            }
+            None => {
+                assert_eq!(entries[0].0, 0);
+                previous.map(|previous| previous.balance)
+            }
+        }
+    }
+
+    fn get_invoice(&self, id: Hash) -> Option<Invoice> {
+        let invoice_info = self
+            .inner
+            .public(ApiKind::Service("cryptocurrency"))
+            .query(&InvoiceQuery { id })
+            .get::<InvoiceInfo>("v1/invoices/info")
+            .unwrap();
+
+        let to_invoice = invoice_info.invoice_proof.to_invoice.check().unwrap();
+        let (_, invoice) = to_invoice.all_entries().find(|(&key, _)| key == id)?;
+        invoice.cloned()
+    }
+
+    fn get_approver(&self, pub_key: PublicKey) -> Option<Approver> {
+        let approver_info = self
+            .inner
+            .public(ApiKind::Service("cryptocurrency"))
+            .query(&WalletQuery { pub_key })
+            .get::<ApproverInfo>("v1/approvers/info")
+            .unwrap();
+
+        let to_approver = approver_info.approver_proof.to_approver.check().unwrap();
+        let (_, approver) = to_approver.all_entries().find(|(&key, _)| key == pub_key)?;
+        approver.cloned()
+    }
+
+    /// Returns hashes of the pending transfers of the given party.
+    fn get_pending_transfers(&self, party: TransferParty, pub_key: PublicKey) -> Vec<Hash> {
+        let info: PendingTransfersInfo = self
+            .inner
+            .public(ApiKind::Service("cryptocurrency"))
+            .query(&PendingTransfersQuery { party, pub_key })
+            .get("v1/transfers/pending")
+            .unwrap();
+
+        let to_root = info.to_root.check().unwrap();
+        match to_root.entries().next() {
+            Some((_, &root)) => {
+                let transfers = info.transfers.unwrap().check().unwrap();
+                assert_eq!(transfers.merkle_root(), root);
+                transfers.entries().map(|(&hash, _)| hash).collect()
+            }
+            None => Vec::new(),
+        }
+    }
+
+    /// Creates a wallet named "Approver" and registers it as an approver staking 50 units.
+    /// Commits all transactions in the pool and creates two blocks.
+    fn create_approver(&self, testkit: &mut TestKit) -> (PublicKey, SecretKey) {
+        let (tx, key) = self.create_wallet("Approver");
+        testkit.create_block();
+        self.assert_tx_status(tx.hash(), &json!({ "type": "success" }));
+
+        let tx_register = RegisterApprover::sign(&tx.author(), 50, 0, &key);
+        self.send_tx(&tx_register);
+        testkit.create_block();
+        self.assert_tx_status(tx_register.hash(), &json!({ "type": "success" }));
+        (tx.author(), key)
+    }
+
+    /// Sends a transfer transaction over HTTP and checks the synchronous result.
+    fn transfer(&self, tx: &Signed<RawTransaction>) {
+        let data = messages::to_hex_string(&tx);
+        let tx_info: TransactionResponse = self
+            .inner
+            .public(ApiKind::Explorer)
+            .query(&json!({ "tx_body": data }))
+            .post("v1/transactions")
+            .unwrap();
+        assert_eq!(tx_info.tx_hash, tx.hash());
+    }
+
+    /// Sends a approve transaction over HTTP and checks the synchronous result.
+    fn approve(&self, tx: &Signed<RawTransaction>) {
+        let data = messages::to_hex_string(&tx);
+        let tx_info: TransactionResponse = self
+            .inner
+            .public(ApiKind::Explorer)
+            .query(&json!({ "tx_body": data }))
+            .post("v1/transactions")
+            .unwrap();
+        assert_eq!(tx_info.tx_hash, tx.hash());
+    }
+
+    /// Sends an arbitrary transaction over HTTP and checks the synchronous result.
+    fn send_tx(&self, tx: &Signed<RawTransaction>) {
+        let data = messages::to_hex_string(&tx);
+        let tx_info: TransactionResponse = self
+            .inner
+            .public(ApiKind::Explorer)
+            .query(&json!({ "tx_body": data }))
+            .post("v1/transactions")
+            .unwrap();
+        assert_eq!(tx_info.tx_hash, tx.hash());
+    }
+
+    /// Asserts that a wallet with the specified public key is not known to the blockchain.
+    fn assert_no_wallet(&self, pub_key: PublicKey) {
+        let wallet_info: WalletInfo = self
+            .inner
+            .public(ApiKind::Service("cryptocurrency"))
+            .query(&WalletQuery { pub_key })
+            .get("v1/wallets/info")
+            .unwrap();
+
+        let to_wallet = wallet_info.wallet_proof.to_wallet.check().unwrap();
+        assert!(to_wallet.missing_keys().any(|&key| key == pub_key))
+    }
+
+    /// Asserts that the transaction with the given hash has a specified status.
+    fn assert_tx_status(&self, tx_hash: Hash, expected_status: &serde_json::Value) {
+        let info: serde_json::Value = self
+            .inner
+            .public(ApiKind::Explorer)
+            .query(&TransactionQuery::new(tx_hash))
+            .get("v1/transactions")
+            .unwrap();
+
+        if let serde_json::Value::Object(mut info) = info {
+            let tx_status = info.remove("status").unwrap();
+            assert_eq!(tx_status, *expected_status);
+        } else {
+            panic!("Invalid transaction info format, object expected");
+        }
+    }
+}
+
+/// Creates a testkit together with the API wrapper defined above.
+fn create_testkit() -> (TestKit, CryptocurrencyApi) {
+    let testkit = TestKitBuilder::validator().with_service(Service).create();
     let api = CryptocurrencyApi {
         inner: testkit.api(),
     };
     (testkit, api)
-}
\ No newline at end of file
+}